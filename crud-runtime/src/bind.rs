@@ -0,0 +1,234 @@
+use crate::{CrudError, Val};
+use sqlx::database::HasArguments;
+use sqlx::query::{Query, QueryAs, QueryScalar};
+use sqlx::{Database, Encode, Type};
+
+/// 把 `Val` 绑定到 sqlx 的查询 (`query` / `query_as` / `query_scalar`) 上, 生成的方法都通过它绑定条件参数
+///
+/// ```ignore
+/// let builder = sqlx::query_as::<_, User>(&sql).bind_vals(&cond.args)?;
+/// ```
+pub trait BindVal<'q>: Sized {
+    /// 绑定一个值, 数据库不支持该类型的值时返回错误 (不会跳过, 以免后面的占位符错位)
+    fn bind_val(self, val: &'q Val) -> Result<Self, CrudError>;
+
+    /// 依次绑定多个值
+    fn bind_vals<I>(self, vals: I) -> Result<Self, CrudError>
+    where
+        I: IntoIterator<Item = &'q Val>,
+    {
+        vals.into_iter().try_fold(self, Self::bind_val)
+    }
+}
+
+/// sqlx 的查询构造器
+pub trait Bind<'q, DB: Database>: Sized {
+    fn bind_one<T: 'q + Send + Encode<'q, DB> + Type<DB>>(self, value: T) -> Self;
+}
+
+impl<'q, DB: Database> Bind<'q, DB> for Query<'q, DB, <DB as HasArguments<'q>>::Arguments> {
+    fn bind_one<T: 'q + Send + Encode<'q, DB> + Type<DB>>(self, value: T) -> Self {
+        self.bind(value)
+    }
+}
+
+impl<'q, DB: Database, O> Bind<'q, DB> for QueryAs<'q, DB, O, <DB as HasArguments<'q>>::Arguments> {
+    fn bind_one<T: 'q + Send + Encode<'q, DB> + Type<DB>>(self, value: T) -> Self {
+        self.bind(value)
+    }
+}
+
+impl<'q, DB: Database, O> Bind<'q, DB>
+    for QueryScalar<'q, DB, O, <DB as HasArguments<'q>>::Arguments>
+{
+    fn bind_one<T: 'q + Send + Encode<'q, DB> + Type<DB>>(self, value: T) -> Self {
+        self.bind(value)
+    }
+}
+
+/// 各数据库绑定 `Val` 的方式
+pub trait BindDb: Database {
+    fn bind_val<'q, B: Bind<'q, Self>>(builder: B, val: &'q Val) -> Result<B, CrudError>;
+}
+
+/// 各数据库都支持的值在这里绑定, 其余的分支由各数据库提供
+macro_rules! impl_bind_db {
+    ($($feature:literal => $db:ty, |$b:ident| { $($arms:tt)* })*) => {
+        $(
+            #[cfg(feature = $feature)]
+            impl BindDb for $db {
+                fn bind_val<'q, B: Bind<'q, Self>>($b: B, val: &'q Val) -> Result<B, CrudError> {
+                    Ok(match val {
+                        Val::I8(v) => $b.bind_one(*v),
+                        Val::I16(v) => $b.bind_one(*v),
+                        Val::I32(v) => $b.bind_one(*v),
+                        Val::I64(v) => $b.bind_one(*v),
+                        Val::F32(v) => $b.bind_one(*v),
+                        Val::F64(v) => $b.bind_one(*v),
+                        Val::Str(v) => $b.bind_one(v.as_str()),
+                        Val::S(v) => $b.bind_one(*v),
+                        Val::Bool(v) => $b.bind_one(*v),
+                        #[cfg(feature = "uuid")]
+                        Val::Uuid(v) => $b.bind_one(*v),
+                        #[cfg(any(feature = "chrono", feature = "time"))]
+                        Val::Timestamp(v) => $b.bind_one(*v),
+                        #[cfg(any(feature = "chrono", feature = "time"))]
+                        Val::Date(v) => $b.bind_one(*v),
+                        #[cfg(feature = "serde_json")]
+                        Val::Json(v) => $b.bind_one(sqlx::types::Json(v)),
+                        Val::Bytes(v) => $b.bind_one(v.as_slice()),
+                        $($arms)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+/// u64 转换为 i64, 超出范围时返回 `CrudError::Overflow`
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+fn checked_i64(v: u64) -> Result<i64, CrudError> {
+    i64::try_from(v).map_err(|_| CrudError::Overflow(v))
+}
+
+/// PostgreSQL 不指定类型 (OID 0) 的 NULL, 由数据库根据上下文推断类型
+#[cfg(feature = "postgres")]
+struct PgNull;
+
+#[cfg(feature = "postgres")]
+impl Type<sqlx::Postgres> for PgNull {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_oid(sqlx::postgres::types::Oid(0))
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Encode<'_, sqlx::Postgres> for PgNull {
+    fn encode_by_ref(&self, _buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        sqlx::encode::IsNull::Yes
+    }
+}
+
+/// 把数组的元素转换为同一类型后绑定, NULL 元素为 None
+#[cfg(feature = "postgres")]
+macro_rules! bind_array {
+    ($builder:ident, $vals:ident, $($pat:pat => $e:expr),+ $(,)?) => {
+        $builder.bind_one(
+            $vals
+                .iter()
+                .map(|v| match v {
+                    Val::Null => Ok(None),
+                    $($pat => Ok(Some($e)),)+
+                    _ => Err(CrudError::Unsupported("元素类型不一致的数组")),
+                })
+                .collect::<Result<Vec<_>, CrudError>>()?,
+        )
+    };
+}
+
+/// PostgreSQL 数组: 整数为 `bigint[]`, 浮点数为 `double precision[]`;
+/// 空数组和只有 NULL 的数组为 `text[]`, 需要时在 SQL 中转换类型 (`id = ANY($1::bigint[])`)
+#[cfg(feature = "postgres")]
+fn bind_pg_array<'q, B: Bind<'q, sqlx::Postgres>>(
+    builder: B,
+    vals: &'q [Val],
+) -> Result<B, CrudError> {
+    Ok(match vals.iter().find(|v| **v != Val::Null) {
+        None => bind_array!(builder, vals, Val::Str(v) => v.as_str()),
+        Some(
+            Val::I8(_)
+            | Val::U8(_)
+            | Val::I16(_)
+            | Val::U16(_)
+            | Val::I32(_)
+            | Val::U32(_)
+            | Val::I64(_)
+            | Val::U64(_),
+        ) => bind_array!(builder, vals,
+            Val::I8(v) => i64::from(*v),
+            Val::U8(v) => i64::from(*v),
+            Val::I16(v) => i64::from(*v),
+            Val::U16(v) => i64::from(*v),
+            Val::I32(v) => i64::from(*v),
+            Val::U32(v) => i64::from(*v),
+            Val::I64(v) => *v,
+            Val::U64(v) => checked_i64(*v)?,
+        ),
+        Some(Val::F32(_) | Val::F64(_)) => bind_array!(builder, vals,
+            Val::F32(v) => f64::from(*v),
+            Val::F64(v) => *v,
+        ),
+        Some(Val::Str(_) | Val::S(_)) => bind_array!(builder, vals,
+            Val::Str(v) => v.as_str(),
+            Val::S(v) => *v,
+        ),
+        Some(Val::Bool(_)) => bind_array!(builder, vals, Val::Bool(v) => *v),
+        #[cfg(feature = "uuid")]
+        Some(Val::Uuid(_)) => bind_array!(builder, vals, Val::Uuid(v) => *v),
+        #[cfg(any(feature = "chrono", feature = "time"))]
+        Some(Val::Timestamp(_)) => bind_array!(builder, vals, Val::Timestamp(v) => *v),
+        #[cfg(any(feature = "chrono", feature = "time"))]
+        Some(Val::Date(_)) => bind_array!(builder, vals, Val::Date(v) => *v),
+        #[cfg(feature = "rust_decimal")]
+        Some(Val::Decimal(_)) => bind_array!(builder, vals, Val::Decimal(v) => *v),
+        #[cfg(feature = "serde_json")]
+        Some(Val::Json(_)) => bind_array!(builder, vals, Val::Json(v) => sqlx::types::Json(v)),
+        Some(Val::Bytes(_)) => bind_array!(builder, vals, Val::Bytes(v) => v.as_slice()),
+        Some(Val::Array(_) | Val::Null) => return Err(CrudError::Unsupported("多维数组")),
+    })
+}
+
+impl_bind_db! {
+    // 没有无符号整数: 转换为更宽的有符号整数
+    "postgres" => sqlx::Postgres, |builder| {
+        Val::U8(v) => builder.bind_one(i16::from(*v)),
+        Val::U16(v) => builder.bind_one(i32::from(*v)),
+        Val::U32(v) => builder.bind_one(i64::from(*v)),
+        Val::U64(v) => builder.bind_one(checked_i64(*v)?),
+        #[cfg(feature = "rust_decimal")]
+        Val::Decimal(v) => builder.bind_one(*v),
+        Val::Array(v) => bind_pg_array(builder, v)?,
+        Val::Null => builder.bind_one(PgNull),
+    }
+    "sqlite" => sqlx::Sqlite, |builder| {
+        Val::U8(v) => builder.bind_one(i16::from(*v)),
+        Val::U16(v) => builder.bind_one(i32::from(*v)),
+        Val::U32(v) => builder.bind_one(i64::from(*v)),
+        Val::U64(v) => builder.bind_one(checked_i64(*v)?),
+        #[cfg(feature = "rust_decimal")]
+        Val::Decimal(_) => return Err(CrudError::Unsupported("Decimal")),
+        Val::Array(_) => return Err(CrudError::Unsupported("Array")),
+        Val::Null => builder.bind_one(None::<String>),
+    }
+    // 支持无符号整数, 直接绑定
+    "mysql" => sqlx::MySql, |builder| {
+        Val::U8(v) => builder.bind_one(*v),
+        Val::U16(v) => builder.bind_one(*v),
+        Val::U32(v) => builder.bind_one(*v),
+        Val::U64(v) => builder.bind_one(*v),
+        #[cfg(feature = "rust_decimal")]
+        Val::Decimal(v) => builder.bind_one(*v),
+        Val::Array(_) => return Err(CrudError::Unsupported("Array")),
+        Val::Null => builder.bind_one(None::<String>),
+    }
+}
+
+impl<'q, DB: BindDb> BindVal<'q> for Query<'q, DB, <DB as HasArguments<'q>>::Arguments> {
+    fn bind_val(self, val: &'q Val) -> Result<Self, CrudError> {
+        DB::bind_val(self, val)
+    }
+}
+
+impl<'q, DB: BindDb, O> BindVal<'q> for QueryAs<'q, DB, O, <DB as HasArguments<'q>>::Arguments> {
+    fn bind_val(self, val: &'q Val) -> Result<Self, CrudError> {
+        DB::bind_val(self, val)
+    }
+}
+
+impl<'q, DB: BindDb, O> BindVal<'q>
+    for QueryScalar<'q, DB, O, <DB as HasArguments<'q>>::Arguments>
+{
+    fn bind_val(self, val: &'q Val) -> Result<Self, CrudError> {
+        DB::bind_val(self, val)
+    }
+}
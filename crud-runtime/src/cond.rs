@@ -0,0 +1,484 @@
+use crate::{CrudError, Dialect, Expr, Val};
+
+/// 默认每页记录数
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// 条件片段: SQL 文本、占位符或依方言而定的运算符
+#[derive(Debug, Clone)]
+pub(crate) enum Part {
+    Sql(String),
+    Arg,
+    /// PostgreSQL 为 `ILIKE`, 其他数据库的 `LIKE` 默认不区分大小写
+    ILike,
+}
+
+/// 软删除记录的查询范围
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Deleted {
+    /// 排除已删除的记录
+    #[default]
+    Exclude,
+    /// 包括已删除的记录
+    Include,
+    /// 只查询已删除的记录
+    Only,
+}
+
+/// 查询条件: WHERE / ORDER BY / 分页
+///
+/// ```ignore
+/// let cond = Cond::new()
+///     .eq("status", 1)
+///     .like("name", "%a%")
+///     .order_by("id DESC")
+///     .page(2, 20);
+/// let (rows, total) = User::get_all_by_cond(&pool, &cond).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cond {
+    /// 绑定参数, 与条件中的占位符按顺序一一对应
+    pub args: Vec<Val>,
+    wheres: Vec<Vec<Part>>,
+    order_by: Option<String>,
+    page: i64,
+    page_size: i64,
+    deleted: Deleted,
+    with_total: bool,
+    /// `raw` 的占位符个数与参数个数不一致, 执行时返回 `CrudError::Validation`
+    error: Option<String>,
+}
+
+impl Default for Cond {
+    fn default() -> Self {
+        Self {
+            args: vec![],
+            wheres: vec![],
+            order_by: None,
+            page: 1,
+            page_size: DEFAULT_PAGE_SIZE,
+            deleted: Deleted::Exclude,
+            with_total: false,
+            error: None,
+        }
+    }
+}
+
+impl From<Expr> for Cond {
+    fn from(expr: Expr) -> Self {
+        Cond::new().filter(expr)
+    }
+}
+
+impl Cond {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(mut self, column: &str, op: &str, val: Val) -> Self {
+        self.wheres
+            .push(vec![Part::Sql(format!("{} {} ", column, op)), Part::Arg]);
+        self.args.push(val);
+        self
+    }
+
+    /// `column = $n`
+    pub fn eq(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, "=", val.into())
+    }
+
+    /// `column <> $n`
+    pub fn ne(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, "<>", val.into())
+    }
+
+    /// `column > $n`
+    pub fn gt(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, ">", val.into())
+    }
+
+    /// `column >= $n`
+    pub fn gte(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, ">=", val.into())
+    }
+
+    /// `column < $n`
+    pub fn lt(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, "<", val.into())
+    }
+
+    /// `column <= $n`
+    pub fn lte(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, "<=", val.into())
+    }
+
+    /// `column LIKE $n`
+    pub fn like(self, column: &str, val: impl Into<Val>) -> Self {
+        self.push(column, "LIKE", val.into())
+    }
+
+    /// `column ILIKE $n`, MySQL / SQLite 使用 `LIKE`
+    pub fn ilike(mut self, column: &str, val: impl Into<Val>) -> Self {
+        self.wheres.push(vec![
+            Part::Sql(format!("{} ", column)),
+            Part::ILike,
+            Part::Sql(" ".to_owned()),
+            Part::Arg,
+        ]);
+        self.args.push(val.into());
+        self
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(mut self, column: &str) -> Self {
+        self.wheres
+            .push(vec![Part::Sql(format!("{} IS NULL", column))]);
+        self
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(mut self, column: &str) -> Self {
+        self.wheres
+            .push(vec![Part::Sql(format!("{} IS NOT NULL", column))]);
+        self
+    }
+
+    /// `column IN ($n, $n+1, ...)`, 列表为空时条件恒为假
+    pub fn in_list<V: Into<Val>>(
+        mut self,
+        column: &str,
+        vals: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let mut parts = vec![Part::Sql(format!("{} IN (", column))];
+        for (i, v) in vals.into_iter().enumerate() {
+            if i > 0 {
+                parts.push(Part::Sql(",".to_owned()));
+            }
+            parts.push(Part::Arg);
+            self.args.push(v.into());
+        }
+        if parts.len() == 1 {
+            parts = vec![Part::Sql("1 = 0".to_owned())];
+        } else {
+            parts.push(Part::Sql(")".to_owned()));
+        }
+        self.wheres.push(parts);
+        self
+    }
+
+    /// 类型化的条件, 由生成的 `columns()` 构造: `Cond::new().filter(User::columns().age.gt(18))`
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.wheres.push(expr.parts);
+        self.args.extend(expr.args);
+        self
+    }
+
+    /// 原始条件, 使用 `?` 作为占位符, 参数个数需要与 `?` 个数一致 (不一致时执行返回 `CrudError::Validation`)
+    ///
+    /// 引号 (`'...'` / `"..."` / `` `...` ``) 中的 `?` 不是占位符; 其他位置的 `?` 本身 (如 PostgreSQL jsonb 的 `?` / `?|`) 写作 `??`
+    pub fn raw(mut self, sql: &str, args: impl IntoIterator<Item = Val>) -> Self {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut quote = None;
+        let mut placeholders = 0;
+        let mut chars = sql.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"' | '`') => quote = Some(c),
+                (None, '?') if chars.peek() == Some(&'?') => {
+                    chars.next();
+                }
+                (None, '?') => {
+                    if !text.is_empty() {
+                        parts.push(Part::Sql(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Arg);
+                    placeholders += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            text.push(c);
+        }
+        if !text.is_empty() {
+            parts.push(Part::Sql(text));
+        }
+        let args: Vec<Val> = args.into_iter().collect();
+        if placeholders != args.len() && self.error.is_none() {
+            self.error = Some(format!(
+                "条件 {} 有 {} 个占位符, 但传入了 {} 个参数",
+                sql,
+                placeholders,
+                args.len()
+            ));
+        }
+        self.wheres.push(parts);
+        self.args.extend(args);
+        self
+    }
+
+    /// 排序, 例如 `id DESC` / `name, created DESC`; 列只能是结构体的字段 (字段名或列名), 执行时校验
+    pub fn order_by(mut self, order_by: &str) -> Self {
+        self.order_by = Some(order_by.to_owned());
+        self
+    }
+
+    /// 分页, 页码从 1 开始
+    pub fn page(mut self, page: i64, page_size: i64) -> Self {
+        self.page = page;
+        self.page_size = page_size;
+        self
+    }
+
+    /// 游标分页 (`get_page_after`) 时同时统计总数
+    pub fn with_total(mut self) -> Self {
+        self.with_total = true;
+        self
+    }
+
+    /// 软删除的表: 包括已删除的记录
+    pub fn with_deleted(mut self) -> Self {
+        self.deleted = Deleted::Include;
+        self
+    }
+
+    /// 软删除的表: 只查询已删除的记录
+    pub fn only_deleted(mut self) -> Self {
+        self.deleted = Deleted::Only;
+        self
+    }
+
+    /// 生成 WHERE 后面的条件 (PostgreSQL), 没有条件时返回空字符串
+    pub fn build(&self) -> String {
+        self.build_for(Dialect::Postgres)
+    }
+
+    /// 依据方言生成 WHERE 后面的条件; 每个条件加上括号后以 AND 连接, `raw` 中的 OR 不会影响其他条件
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut index = 0;
+        let wheres: Vec<String> = self
+            .wheres
+            .iter()
+            .map(|parts| {
+                let mut sql = String::from("(");
+                for part in parts {
+                    match part {
+                        Part::Sql(s) => sql.push_str(s),
+                        Part::Arg => {
+                            index += 1;
+                            sql.push_str(&dialect.placeholder(index));
+                        }
+                        Part::ILike => sql.push_str(match dialect {
+                            Dialect::Postgres => "ILIKE",
+                            _ => "LIKE",
+                        }),
+                    }
+                }
+                sql.push(')');
+                sql
+            })
+            .collect();
+        wheres.join(" AND ")
+    }
+
+    /// 生成条件并加上软删除的过滤; alive / dead 分别为未删除、已删除的条件
+    pub fn build_soft_delete(&self, dialect: Dialect, alive: &str, dead: &str) -> String {
+        let filter = match self.deleted {
+            Deleted::Exclude => alive,
+            Deleted::Only => dead,
+            Deleted::Include => return self.build_for(dialect),
+        };
+        let sql = self.build_for(dialect);
+        if sql.is_empty() {
+            filter.to_owned()
+        } else {
+            format!("{} AND {}", filter, sql)
+        }
+    }
+
+    /// `raw` 的占位符个数与参数个数不一致时返回 `CrudError::Validation`; 生成的方法执行前都会检查
+    pub fn check(&self) -> Result<(), CrudError> {
+        match &self.error {
+            Some(error) => Err(CrudError::Validation(error.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// 是否有 WHERE 条件
+    pub fn has_args(&self) -> bool {
+        !self.wheres.is_empty()
+    }
+
+    /// 分页: (页码, 每页记录数)
+    pub fn get_limits(&self) -> (i64, i64) {
+        (self.page.max(1), self.page_size.max(1))
+    }
+
+    /// 排序
+    pub fn get_order_by(&self) -> Option<&str> {
+        self.order_by.as_deref()
+    }
+
+    /// 游标分页时是否统计总数
+    pub fn need_total(&self) -> bool {
+        self.with_total
+    }
+
+    /// 绑定参数个数
+    pub fn arg_count(&self) -> usize {
+        self.args.len()
+    }
+}
+
+/// 校验排序并转换为 SQL: `列 [ASC|DESC] [NULLS FIRST|LAST], ...`
+///
+/// column 把字段名或列名转换为 SQL 中使用的列名 (生成的 `{结构体}Column::from_name`), 不认识的列或多余的内容返回错误,
+/// 因此来自请求参数的排序不会被拼接到 SQL 中
+pub fn order_by_sql(
+    order_by: &str,
+    column: impl Fn(&str) -> Option<&'static str>,
+) -> Result<String, CrudError> {
+    let invalid = |term: &str| CrudError::Validation(format!("无效的排序: {}", term));
+    let mut terms = vec![];
+    for term in order_by.split(',') {
+        let mut words = term.split_whitespace();
+        let name = words.next().ok_or_else(|| invalid(order_by))?;
+        let mut sql = column(name).ok_or_else(|| invalid(name))?.to_owned();
+        let rest: Vec<String> = words.map(|w| w.to_ascii_uppercase()).collect();
+        let valid = matches!(
+            rest.iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .as_slice(),
+            [] | ["ASC" | "DESC"]
+                | ["NULLS", "FIRST" | "LAST"]
+                | ["ASC" | "DESC", "NULLS", "FIRST" | "LAST"]
+        );
+        if !valid {
+            return Err(invalid(term.trim()));
+        }
+        for word in &rest {
+            sql.push(' ');
+            sql.push_str(word);
+        }
+        terms.push(sql);
+    }
+    Ok(terms.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_or_is_parenthesized() {
+        let cond = Cond::new()
+            .raw("a = ? OR b = ?", [Val::I32(1), Val::I32(2)])
+            .eq("c", 3);
+        assert_eq!(cond.build(), "(a = $1 OR b = $2) AND (c = $3)");
+        assert_eq!(
+            cond.build_for(Dialect::MySql),
+            "(a = ? OR b = ?) AND (c = ?)"
+        );
+        assert_eq!(cond.args, vec![Val::I32(1), Val::I32(2), Val::I32(3)]);
+    }
+
+    #[test]
+    fn soft_delete_filter() {
+        let cond = Cond::new().raw("a = ? OR b = ?", [Val::I32(1), Val::I32(2)]);
+        assert_eq!(
+            cond.build_soft_delete(
+                Dialect::Sqlite,
+                "deleted_at IS NULL",
+                "deleted_at IS NOT NULL"
+            ),
+            "deleted_at IS NULL AND (a = ?1 OR b = ?2)"
+        );
+        assert_eq!(
+            cond.clone()
+                .with_deleted()
+                .build_soft_delete(Dialect::Sqlite, "x", "y"),
+            "(a = ?1 OR b = ?2)"
+        );
+        assert_eq!(
+            Cond::new()
+                .only_deleted()
+                .build_soft_delete(Dialect::Postgres, "x", "y"),
+            "y"
+        );
+    }
+
+    #[test]
+    fn ilike_and_empty_in_list() {
+        let cond = Cond::new()
+            .ilike("name", "%a%")
+            .in_list("id", Vec::<i64>::new());
+        assert_eq!(cond.build(), "(name ILIKE $1) AND (1 = 0)");
+        assert_eq!(
+            cond.build_for(Dialect::Sqlite),
+            "(name LIKE ?1) AND (1 = 0)"
+        );
+    }
+
+    #[test]
+    fn raw_placeholders() {
+        let cond = Cond::new().raw(
+            "name = '?' AND note = 'it''s ?' AND \"a?\" = ? AND extra ?? 'k' AND tags ??| ?",
+            [Val::I32(1), Val::S("x")],
+        );
+        assert_eq!(
+            cond.build_for(Dialect::Sqlite),
+            "(name = '?' AND note = 'it''s ?' AND \"a?\" = ?1 AND extra ? 'k' AND tags ?| ?2)"
+        );
+        assert!(cond.check().is_ok());
+
+        let cond = Cond::new().raw("name = ?", []);
+        assert!(matches!(cond.check(), Err(CrudError::Validation(_))));
+        let cond = Cond::new()
+            .eq("a", 1)
+            .raw("b = ?", [Val::I32(2), Val::I32(3)])
+            .eq("c", 4);
+        assert!(matches!(cond.check(), Err(CrudError::Validation(_))));
+        assert!(Cond::new().eq("a", 1).check().is_ok());
+    }
+
+    fn column(name: &str) -> Option<&'static str> {
+        match name {
+            "id" => Some("\"id\""),
+            "name" | "user_name" => Some("\"user_name\""),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn order_by_is_whitelisted() {
+        assert_eq!(
+            order_by_sql("name desc, id", column).unwrap(),
+            "\"user_name\" DESC, \"id\""
+        );
+        assert_eq!(
+            order_by_sql("user_name ASC NULLS last", column).unwrap(),
+            "\"user_name\" ASC NULLS LAST"
+        );
+        for order_by in [
+            "",
+            "id,",
+            "password",
+            "id; DROP TABLE users",
+            "id DESC --",
+            "(SELECT 1)",
+            "id ASC DESC",
+            "id NULLS",
+        ] {
+            assert!(
+                matches!(
+                    order_by_sql(order_by, column),
+                    Err(CrudError::Validation(_))
+                ),
+                "{}",
+                order_by
+            );
+        }
+    }
+}
@@ -0,0 +1,103 @@
+/// `COPY ... FROM STDIN` 文本格式的值
+pub trait CopyValue {
+    /// 写入一个值, `\N` 表示 NULL
+    fn write_copy(&self, buf: &mut String);
+}
+
+macro_rules! impl_copy_display {
+    ($($t:ty),*) => {
+        $(
+            impl CopyValue for $t {
+                fn write_copy(&self, buf: &mut String) {
+                    buf.push_str(&self.to_string());
+                }
+            }
+        )*
+    };
+}
+
+impl_copy_display!(i8, i16, i32, i64, u32, f32, f64, bool);
+
+#[cfg(feature = "uuid")]
+impl_copy_display!(uuid::Uuid);
+
+#[cfg(feature = "chrono")]
+impl_copy_display!(chrono::NaiveDateTime, chrono::NaiveDate);
+
+#[cfg(feature = "time")]
+impl_copy_display!(time::PrimitiveDateTime, time::Date);
+
+#[cfg(feature = "rust_decimal")]
+impl_copy_display!(rust_decimal::Decimal);
+
+/// timestamptz: RFC 3339
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> CopyValue for chrono::DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn write_copy(&self, buf: &mut String) {
+        buf.push_str(&self.to_rfc3339());
+    }
+}
+
+/// timestamptz: `2024-01-02 03:04:05.000006 +00:00:00`
+#[cfg(feature = "time")]
+impl CopyValue for time::OffsetDateTime {
+    fn write_copy(&self, buf: &mut String) {
+        buf.push_str(&self.to_string());
+    }
+}
+
+/// json / jsonb: 序列化后按字符串转义
+#[cfg(feature = "serde_json")]
+impl CopyValue for serde_json::Value {
+    fn write_copy(&self, buf: &mut String) {
+        self.to_string().write_copy(buf);
+    }
+}
+
+impl CopyValue for str {
+    fn write_copy(&self, buf: &mut String) {
+        for c in self.chars() {
+            match c {
+                '\\' => buf.push_str("\\\\"),
+                '\t' => buf.push_str("\\t"),
+                '\n' => buf.push_str("\\n"),
+                '\r' => buf.push_str("\\r"),
+                _ => buf.push(c),
+            }
+        }
+    }
+}
+
+impl CopyValue for String {
+    fn write_copy(&self, buf: &mut String) {
+        self.as_str().write_copy(buf);
+    }
+}
+
+/// bytea: `\\x` 加十六进制
+impl CopyValue for Vec<u8> {
+    fn write_copy(&self, buf: &mut String) {
+        buf.push_str("\\\\x");
+        for b in self {
+            buf.push_str(&format!("{:02x}", b));
+        }
+    }
+}
+
+impl<T: CopyValue + ?Sized> CopyValue for &T {
+    fn write_copy(&self, buf: &mut String) {
+        (**self).write_copy(buf);
+    }
+}
+
+impl<T: CopyValue> CopyValue for Option<T> {
+    fn write_copy(&self, buf: &mut String) {
+        match self {
+            Some(v) => v.write_copy(buf),
+            None => buf.push_str("\\N"),
+        }
+    }
+}
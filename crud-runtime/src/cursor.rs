@@ -0,0 +1,355 @@
+use crate::{CrudError, Dialect, Val};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 游标中保存的排序值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Key {
+    I(i64),
+    U(u64),
+    F(f64),
+    S(String),
+    B(bool),
+}
+
+/// 游标只保存可以用于游标分页的字段类型的值
+impl TryFrom<Val> for Key {
+    type Error = CrudError;
+
+    fn try_from(v: Val) -> Result<Self, Self::Error> {
+        Ok(match v {
+            Val::I8(v) => Key::I(v.into()),
+            Val::U8(v) => Key::I(v.into()),
+            Val::I16(v) => Key::I(v.into()),
+            Val::U16(v) => Key::I(v.into()),
+            Val::I32(v) => Key::I(v.into()),
+            Val::U32(v) => Key::I(v.into()),
+            Val::I64(v) => Key::I(v),
+            Val::U64(v) => Key::U(v),
+            Val::F32(v) => Key::F(v.into()),
+            Val::F64(v) => Key::F(v),
+            Val::Str(v) => Key::S(v),
+            Val::S(v) => Key::S(v.to_owned()),
+            Val::Bool(v) => Key::B(v),
+            v => return Err(CrudError::Validation(format!("游标不能保存 {:?}", v))),
+        })
+    }
+}
+
+impl From<&Key> for Val {
+    fn from(k: &Key) -> Self {
+        match k {
+            Key::I(v) => Val::I64(*v),
+            Key::U(v) => Val::U64(*v),
+            Key::F(v) => Val::F64(*v),
+            Key::S(v) => Val::Str(v.clone()),
+            Key::B(v) => Val::Bool(*v),
+        }
+    }
+}
+
+/// 游标分页的游标: 一页第一条 / 最后一条记录的排序列和主键的值
+///
+/// 对调用方不透明, 可以用 serde 序列化, 也可以用 `to_string()` / `parse()` 转换为 URL 安全的字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    keys: Vec<Key>,
+    /// true 表示取该位置之前的一页
+    before: bool,
+}
+
+impl Cursor {
+    /// 取 keys 之后的一页; keys 只能是整数、浮点数、字符串和布尔值 (可以用于游标分页的字段类型), 否则返回 `CrudError::Validation`
+    pub fn after(keys: Vec<Val>) -> Result<Self, CrudError> {
+        Ok(Self {
+            keys: keys
+                .into_iter()
+                .map(Key::try_from)
+                .collect::<Result<_, _>>()?,
+            before: false,
+        })
+    }
+
+    /// 取 keys 之前的一页, keys 的要求与 `after` 相同
+    pub fn before(keys: Vec<Val>) -> Result<Self, CrudError> {
+        Ok(Self {
+            before: true,
+            ..Self::after(keys)?
+        })
+    }
+
+    /// 是否向前翻页
+    pub fn is_before(&self) -> bool {
+        self.before
+    }
+
+    /// 绑定参数
+    pub fn values(&self) -> Vec<Val> {
+        self.keys.iter().map(Val::from).collect()
+    }
+}
+
+fn hex(s: &str) -> String {
+    s.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<String> {
+    let bytes = (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+impl fmt::Display for Cursor {
+    /// 格式: 方向 (`a` / `b`) 后接各个值, 以 `.` 分隔; 字符串以十六进制保存
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.before { "b" } else { "a" })?;
+        for key in &self.keys {
+            match key {
+                Key::I(v) => write!(f, ".i{}", v)?,
+                Key::U(v) => write!(f, ".u{}", v)?,
+                Key::F(v) => write!(f, ".f{:x}", v.to_bits())?,
+                Key::S(v) => write!(f, ".s{}", hex(v))?,
+                Key::B(v) => write!(f, ".b{}", u8::from(*v))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = CrudError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CrudError::Validation(format!("无效的游标: {}", s));
+        let mut parts = s.split('.');
+        let before = match parts.next() {
+            Some("a") => false,
+            Some("b") => true,
+            _ => return Err(invalid()),
+        };
+        let keys = parts
+            .map(|part| {
+                let (tag, v) = (part.get(..1)?, part.get(1..)?);
+                match tag {
+                    "i" => v.parse().ok().map(Key::I),
+                    "u" => v.parse().ok().map(Key::U),
+                    "f" => u64::from_str_radix(v, 16)
+                        .ok()
+                        .map(|bits| Key::F(f64::from_bits(bits))),
+                    "s" => unhex(v).map(Key::S),
+                    "b" => match v {
+                        "0" => Some(Key::B(false)),
+                        "1" => Some(Key::B(true)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<Key>>>()
+            .ok_or_else(invalid)?;
+        if keys.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { keys, before })
+    }
+}
+
+/// 游标分页的结果
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub rows: Vec<T>,
+    /// 下一页的游标, 没有更多记录时为 None
+    pub next: Option<Cursor>,
+    /// 上一页的游标, 第一页为 None
+    pub prev: Option<Cursor>,
+    /// 总数, 只有 `Cond::with_total()` 时统计
+    pub total: Option<i64>,
+}
+
+/// 游标分页每页记录数的上限
+pub const MAX_PAGE_LIMIT: i64 = 1000;
+
+/// 游标分页的每页记录数: 限制在 1 到 `MAX_PAGE_LIMIT` 之间, 可以直接使用来自请求参数的值
+pub fn keyset_limit(limit: i64) -> i64 {
+    limit.clamp(1, MAX_PAGE_LIMIT)
+}
+
+/// 解析 `Cond::order_by` 作为游标分页的排序列: `列` / `列 ASC` / `列 DESC`
+///
+/// 返回 (排序列, 是否降序); 未指定排序时为 None (只按主键排序)
+pub fn keyset_order(order_by: Option<&str>) -> Result<Option<(&str, bool)>, CrudError> {
+    let Some(order_by) = order_by else {
+        return Ok(None);
+    };
+    let mut words = order_by.split_whitespace();
+    let column = words.next();
+    let desc = match words.next() {
+        None => false,
+        Some(d) if d.eq_ignore_ascii_case("ASC") => false,
+        Some(d) if d.eq_ignore_ascii_case("DESC") => true,
+        Some(_) => return Err(keyset_order_error(order_by)),
+    };
+    match (column, words.next()) {
+        (Some(column), None) if !column.contains(',') => Ok(Some((column, desc))),
+        _ => Err(keyset_order_error(order_by)),
+    }
+}
+
+fn keyset_order_error(order_by: &str) -> CrudError {
+    CrudError::Validation(format!(
+        "游标分页只支持按单列排序 (列 [ASC|DESC]): {}",
+        order_by
+    ))
+}
+
+/// 游标分页的 WHERE 条件和 ORDER BY
+///
+/// columns 为排序列和主键, arg_count 为已有的绑定参数个数; 条件为 `(列, 主键) > (值, 值)`,
+/// 向前翻页时比较和排序的方向都反过来
+pub fn keyset_sql(
+    dialect: Dialect,
+    columns: &[&str],
+    desc: bool,
+    cursor: Option<&Cursor>,
+    arg_count: usize,
+) -> Result<(Option<String>, String), CrudError> {
+    let before = cursor.map(Cursor::is_before).unwrap_or(false);
+    let reverse = desc != before;
+    let order_by = columns
+        .iter()
+        .map(|c| format!("{} {}", c, if reverse { "DESC" } else { "ASC" }))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let Some(cursor) = cursor else {
+        return Ok((None, order_by));
+    };
+    if cursor.keys.len() != columns.len() {
+        return Err(CrudError::Validation("游标与排序列不一致".to_owned()));
+    }
+    let holders = (1..=columns.len())
+        .map(|i| dialect.placeholder(arg_count + i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "({}) {} ({})",
+        columns.join(", "),
+        if reverse { "<" } else { ">" },
+        holders
+    );
+    Ok((Some(sql), order_by))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_keys_are_rejected() {
+        for v in [
+            Val::Bytes(vec![1]),
+            Val::Null,
+            Val::Array(vec![Val::I32(1)]),
+        ] {
+            assert!(matches!(
+                Cursor::after(vec![Val::I32(1), v.clone()]),
+                Err(CrudError::Validation(_))
+            ));
+            assert!(Cursor::before(vec![v]).is_err());
+        }
+        assert!(Cursor::before(vec![Val::S("a"), Val::U8(1)])
+            .unwrap()
+            .is_before());
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        assert_eq!(keyset_limit(i64::MAX), MAX_PAGE_LIMIT);
+        assert_eq!(keyset_limit(i64::MIN), 1);
+        assert_eq!(keyset_limit(0), 1);
+        assert_eq!(keyset_limit(50), 50);
+        // 生成的代码查询 limit + 1 条记录
+        assert!(keyset_limit(i64::MAX).checked_add(1).is_some());
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor::after(vec![
+            Val::I32(-7),
+            Val::U64(u64::MAX),
+            Val::F64(-1.5),
+            Val::S("a.b 中文"),
+            Val::Bool(true),
+        ])
+        .unwrap();
+        let s = cursor.to_string();
+        assert!(s.starts_with("a.i-7."));
+        assert!(!s.contains(' '));
+        assert_eq!(s.parse::<Cursor>().unwrap(), cursor);
+        assert_eq!(
+            cursor.values(),
+            vec![
+                Val::I64(-7),
+                Val::U64(u64::MAX),
+                Val::F64(-1.5),
+                Val::Str("a.b 中文".to_owned()),
+                Val::Bool(true),
+            ]
+        );
+        let before = Cursor::before(vec![Val::Str(String::new())]).unwrap();
+        assert_eq!(before.to_string().parse::<Cursor>().unwrap(), before);
+        for s in [
+            "", "a", "x.i1", "a.i", "a.ix", "a.s0", "a.sff", "a.b2", "a.q1", "a.i1.",
+        ] {
+            assert!(s.parse::<Cursor>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn keyset_order_accepts_one_column() {
+        assert_eq!(keyset_order(None).unwrap(), None);
+        assert_eq!(keyset_order(Some("name")).unwrap(), Some(("name", false)));
+        assert_eq!(
+            keyset_order(Some("name desc")).unwrap(),
+            Some(("name", true))
+        );
+        for order_by in ["", "a, b", "a,b", "a DESC NULLS LAST", "a sideways"] {
+            assert!(keyset_order(Some(order_by)).is_err(), "{}", order_by);
+        }
+    }
+
+    #[test]
+    fn keyset_sql_reverses_before() {
+        let columns = ["name", "id"];
+        assert_eq!(
+            keyset_sql(Dialect::Postgres, &columns, false, None, 0).unwrap(),
+            (None, "name ASC, id ASC".to_owned())
+        );
+        let after = Cursor::after(vec![Val::S("x"), Val::I64(1)]).unwrap();
+        assert_eq!(
+            keyset_sql(Dialect::Postgres, &columns, false, Some(&after), 2).unwrap(),
+            (
+                Some("(name, id) > ($3, $4)".to_owned()),
+                "name ASC, id ASC".to_owned()
+            )
+        );
+        let before = Cursor::before(vec![Val::S("x"), Val::I64(1)]).unwrap();
+        assert_eq!(
+            keyset_sql(Dialect::Sqlite, &columns, false, Some(&before), 0).unwrap(),
+            (
+                Some("(name, id) < (?1, ?2)".to_owned()),
+                "name DESC, id DESC".to_owned()
+            )
+        );
+        assert_eq!(
+            keyset_sql(Dialect::MySql, &columns, true, Some(&before), 1).unwrap(),
+            (
+                Some("(name, id) > (?, ?)".to_owned()),
+                "name ASC, id ASC".to_owned()
+            )
+        );
+        assert!(keyset_sql(Dialect::Postgres, &["id"], false, Some(&after), 0).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::Dialect;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 建表时字段类型对应的列类型, 自定义类型可以实现该 trait, 或者使用 `#[crud(sql_type = "...")]`
+pub trait SqlType {
+    fn sql_type(dialect: Dialect) -> &'static str;
+}
+
+macro_rules! impl_sql_type {
+    ($($t:ty => $pg:expr, $mysql:expr, $sqlite:expr;)*) => {
+        $(
+            impl SqlType for $t {
+                fn sql_type(dialect: Dialect) -> &'static str {
+                    match dialect {
+                        Dialect::Postgres => $pg,
+                        Dialect::MySql => $mysql,
+                        Dialect::Sqlite => $sqlite,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_sql_type! {
+    i8 => "SMALLINT", "TINYINT", "INTEGER";
+    u8 => "SMALLINT", "TINYINT UNSIGNED", "INTEGER";
+    i16 => "SMALLINT", "SMALLINT", "INTEGER";
+    u16 => "INTEGER", "SMALLINT UNSIGNED", "INTEGER";
+    i32 => "INTEGER", "INT", "INTEGER";
+    u32 => "BIGINT", "INT UNSIGNED", "INTEGER";
+    i64 => "BIGINT", "BIGINT", "INTEGER";
+    u64 => "BIGINT", "BIGINT UNSIGNED", "INTEGER";
+    f32 => "REAL", "FLOAT", "REAL";
+    f64 => "DOUBLE PRECISION", "DOUBLE", "REAL";
+    bool => "BOOLEAN", "BOOLEAN", "BOOLEAN";
+    String => "TEXT", "VARCHAR(255)", "TEXT";
+    Vec<u8> => "BYTEA", "BLOB", "BLOB";
+}
+
+#[cfg(feature = "uuid")]
+impl_sql_type! {
+    uuid::Uuid => "UUID", "BINARY(16)", "BLOB";
+}
+
+#[cfg(feature = "chrono")]
+impl_sql_type! {
+    chrono::DateTime<chrono::Utc> => "TIMESTAMPTZ", "DATETIME(6)", "DATETIME";
+    chrono::NaiveDateTime => "TIMESTAMP", "DATETIME(6)", "DATETIME";
+    chrono::NaiveDate => "DATE", "DATE", "DATE";
+}
+
+#[cfg(feature = "time")]
+impl_sql_type! {
+    time::OffsetDateTime => "TIMESTAMPTZ", "DATETIME(6)", "DATETIME";
+    time::PrimitiveDateTime => "TIMESTAMP", "DATETIME(6)", "DATETIME";
+    time::Date => "DATE", "DATE", "DATE";
+}
+
+#[cfg(feature = "rust_decimal")]
+impl_sql_type! {
+    rust_decimal::Decimal => "NUMERIC", "DECIMAL(38,10)", "NUMERIC";
+}
+
+#[cfg(feature = "serde_json")]
+impl_sql_type! {
+    serde_json::Value => "JSONB", "JSON", "TEXT";
+}
+
+impl<T: SqlType + ?Sized> SqlType for &T {
+    fn sql_type(dialect: Dialect) -> &'static str {
+        T::sql_type(dialect)
+    }
+}
+
+/// 可以为 NULL 由 `Option` 决定, 类型与 T 相同
+impl<T: SqlType> SqlType for Option<T> {
+    fn sql_type(dialect: Dialect) -> &'static str {
+        T::sql_type(dialect)
+    }
+}
+
+/// 生成 sqlx migrate 格式的迁移文件: `{yyyymmddhhmmss}_create_{表名}.sql`
+///
+/// ```ignore
+/// Migrations::new()
+///     .table(User::get_table_name(), User::create_table_sql())
+///     .table(Order::get_table_name(), Order::create_table_sql())
+///     .write("migrations")?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    tables: Vec<(String, String)>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一张表: 表名 (`get_table_name()`) 和建表语句 (`create_table_sql()`)
+    pub fn table(mut self, name: &str, sql: String) -> Self {
+        self.tables.push((name.to_owned(), sql));
+        self
+    }
+
+    /// 写入目录, 每张表一个文件, 版本号依次加 1 以保持顺序;
+    /// 目录中已有同名 (`create_{表名}`) 的迁移时跳过该表. 返回写入的文件
+    pub fn write(&self, dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let mut existing = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if let Some((_, description)) = name.split_once('_') {
+                existing.push(description.trim_end_matches(".sql").to_owned());
+            }
+        }
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut written = vec![];
+        for (name, sql) in &self.tables {
+            let description = format!("create_{}", file_name(name));
+            if existing.contains(&description) {
+                continue;
+            }
+            let path = dir.join(format!(
+                "{}_{}.sql",
+                version(secs + written.len() as u64),
+                description
+            ));
+            std::fs::write(&path, format!("{}\n", sql))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// 表名转换为文件名: 去掉引号, `schema.table` -> `schema_table`
+fn file_name(table: &str) -> String {
+    table
+        .chars()
+        .filter(|c| !matches!(c, '"' | '`'))
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// UTC 时间 `yyyymmddhhmmss`, 与 `sqlx migrate add` 的版本号格式一致
+fn version(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // 公历日期 (Howard Hinnant 的 civil_from_days)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_is_utc_timestamp() {
+        assert_eq!(version(0), "19700101000000");
+        assert_eq!(version(951_782_400), "20000229000000");
+        assert_eq!(version(1_704_164_645), "20240102030405");
+        assert_eq!(version(4_107_542_399), "21000228235959");
+    }
+
+    #[test]
+    fn write_skips_existing_tables() {
+        let dir = std::env::temp_dir().join(format!("crud-migrations-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let migrations = Migrations::new()
+            .table("\"app\".\"users\"", "CREATE TABLE users ()".to_owned())
+            .table("`orders`", "CREATE TABLE orders ()".to_owned());
+        let written = migrations.write(&dir).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names[0].ends_with("_create_app_users.sql"));
+        assert!(names[1].ends_with("_create_orders.sql"));
+        assert!(names[0] < names[1]);
+        assert_eq!(
+            std::fs::read_to_string(&written[1]).unwrap(),
+            "CREATE TABLE orders ()\n"
+        );
+        assert!(migrations.write(&dir).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,27 @@
+/// SQL 方言: 占位符和标识符引号
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl Dialect {
+    /// 第 index 个占位符 (从 1 开始): `$1` / `?` / `?1`
+    pub fn placeholder(self, index: usize) -> String {
+        match self {
+            Dialect::Postgres => format!("${}", index),
+            Dialect::MySql => "?".to_owned(),
+            Dialect::Sqlite => format!("?{}", index),
+        }
+    }
+
+    /// 标识符加引号: `"name"` / `` `name` ``
+    pub fn quote_ident(self, name: &str) -> String {
+        match self {
+            Dialect::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{SchemaReport, ValidationErrors};
+use sqlx::error::ErrorKind;
+
+/// 生成方法返回的错误
+#[derive(Debug, thiserror::Error)]
+pub enum CrudError {
+    /// 记录不存在
+    #[error("记录不存在")]
+    NotFound,
+    /// 乐观锁冲突: 记录已被修改 (或已不存在)
+    #[error("记录已被修改")]
+    Conflict,
+    /// 违反唯一约束
+    #[error("违反唯一约束: {constraint}")]
+    UniqueViolation { constraint: String },
+    /// 违反外键约束
+    #[error("违反外键约束: {constraint}")]
+    ForeignKeyViolation { constraint: String },
+    /// 数据校验失败
+    #[error("数据校验失败: {0}")]
+    Validation(String),
+    /// 字段校验失败 (`#[crud(validate(...))]`)
+    #[error("{0}")]
+    Invalid(#[from] ValidationErrors),
+    /// 无符号整数超出数据库整数的范围 (PostgreSQL / SQLite 的 u64 大于 i64::MAX)
+    #[error("数值 {0} 超出数据库整数的范围")]
+    Overflow(u64),
+    /// 数据库不支持的值 (如 SQLite 的 `Val::Decimal`, MySQL / SQLite 的 `Val::Array`)
+    #[error("数据库不支持该类型的值: {0}")]
+    Unsupported(&'static str),
+    /// 被 `CrudHooks` 取消的操作
+    #[error("操作被拒绝: {0}")]
+    Rejected(String),
+    /// 表结构与结构体不一致 (`verify_schema`)
+    #[error("{0}")]
+    Schema(SchemaReport),
+    /// 其他数据库错误
+    #[error("数据库错误: {0}")]
+    Database(#[source] sqlx::Error),
+}
+
+impl From<sqlx::Error> for CrudError {
+    fn from(err: sqlx::Error) -> Self {
+        let kind = match &err {
+            sqlx::Error::RowNotFound => return CrudError::NotFound,
+            sqlx::Error::Database(e) => {
+                let constraint = e.constraint().unwrap_or_default().to_owned();
+                match e.kind() {
+                    ErrorKind::UniqueViolation => Some(CrudError::UniqueViolation { constraint }),
+                    ErrorKind::ForeignKeyViolation => {
+                        Some(CrudError::ForeignKeyViolation { constraint })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        kind.unwrap_or(CrudError::Database(err))
+    }
+}
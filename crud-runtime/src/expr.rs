@@ -0,0 +1,225 @@
+use crate::cond::Part;
+use crate::Val;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// 可以作为 `Col<T>` 条件值的类型: T 本身, 字符串列还可以使用 `&str`
+pub trait ColValue<T> {
+    fn into_value(self) -> T;
+}
+
+impl<T> ColValue<T> for T {
+    fn into_value(self) -> T {
+        self
+    }
+}
+
+impl ColValue<String> for &str {
+    fn into_value(self) -> String {
+        self.to_owned()
+    }
+}
+
+/// 类型化的列, 由生成的 `columns()` 返回; T 为字段类型 (`Option<T>` 字段为 T)
+///
+/// ```ignore
+/// let c = User::columns();
+/// let cond = Cond::new().filter(c.age.gt(18).and(c.name.ilike("%a%")));
+/// ```
+pub struct Col<T> {
+    name: &'static str,
+    _ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Col<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Col<T> {}
+
+impl<T> fmt::Debug for Col<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Col").field(&self.name).finish()
+    }
+}
+
+impl<T> Col<T> {
+    /// name 为 SQL 中使用的列名 (必要时带引号)
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _ty: PhantomData,
+        }
+    }
+
+    /// SQL 中使用的列名
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(self) -> Expr {
+        Expr::sql(format!("{} IS NULL", self.name))
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(self) -> Expr {
+        Expr::sql(format!("{} IS NOT NULL", self.name))
+    }
+}
+
+impl<T: Into<Val>> Col<T> {
+    fn cmp(self, op: &str, val: impl ColValue<T>) -> Expr {
+        Expr {
+            parts: vec![Part::Sql(format!("{} {} ", self.name, op)), Part::Arg],
+            args: vec![val.into_value().into()],
+        }
+    }
+
+    /// `column = $n`
+    pub fn eq(self, val: impl ColValue<T>) -> Expr {
+        self.cmp("=", val)
+    }
+
+    /// `column <> $n`
+    pub fn ne(self, val: impl ColValue<T>) -> Expr {
+        self.cmp("<>", val)
+    }
+
+    /// `column > $n`
+    pub fn gt(self, val: impl ColValue<T>) -> Expr {
+        self.cmp(">", val)
+    }
+
+    /// `column >= $n`
+    pub fn gte(self, val: impl ColValue<T>) -> Expr {
+        self.cmp(">=", val)
+    }
+
+    /// `column < $n`
+    pub fn lt(self, val: impl ColValue<T>) -> Expr {
+        self.cmp("<", val)
+    }
+
+    /// `column <= $n`
+    pub fn lte(self, val: impl ColValue<T>) -> Expr {
+        self.cmp("<=", val)
+    }
+
+    /// `column IN ($n, $n+1, ...)`, 列表为空时条件恒为假
+    pub fn in_list<V: ColValue<T>>(self, vals: impl IntoIterator<Item = V>) -> Expr {
+        let mut expr = Expr::sql(format!("{} IN (", self.name));
+        for (i, v) in vals.into_iter().enumerate() {
+            if i > 0 {
+                expr.parts.push(Part::Sql(",".to_owned()));
+            }
+            expr.parts.push(Part::Arg);
+            expr.args.push(v.into_value().into());
+        }
+        if expr.args.is_empty() {
+            return Expr::sql("1 = 0".to_owned());
+        }
+        expr.parts.push(Part::Sql(")".to_owned()));
+        expr
+    }
+}
+
+impl Col<String> {
+    /// `column LIKE $n`
+    pub fn like(self, pattern: impl ColValue<String>) -> Expr {
+        self.cmp("LIKE", pattern)
+    }
+
+    /// `column ILIKE $n`, MySQL / SQLite 使用 `LIKE`
+    pub fn ilike(self, pattern: impl ColValue<String>) -> Expr {
+        Expr {
+            parts: vec![
+                Part::Sql(format!("{} ", self.name)),
+                Part::ILike,
+                Part::Sql(" ".to_owned()),
+                Part::Arg,
+            ],
+            args: vec![Val::Str(pattern.into_value())],
+        }
+    }
+}
+
+/// 类型化的条件表达式, 通过 `Cond::filter` 加入查询条件
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub(crate) parts: Vec<Part>,
+    pub(crate) args: Vec<Val>,
+}
+
+impl Expr {
+    fn sql(sql: String) -> Self {
+        Self {
+            parts: vec![Part::Sql(sql)],
+            args: vec![],
+        }
+    }
+
+    fn join(mut self, op: &str, other: Expr) -> Self {
+        self.parts.insert(0, Part::Sql("(".to_owned()));
+        self.parts.push(Part::Sql(format!(" {} ", op)));
+        self.parts.extend(other.parts);
+        self.parts.push(Part::Sql(")".to_owned()));
+        self.args.extend(other.args);
+        self
+    }
+
+    /// `(a AND b)`
+    pub fn and(self, other: Expr) -> Self {
+        self.join("AND", other)
+    }
+
+    /// `(a OR b)`
+    pub fn or(self, other: Expr) -> Self {
+        self.join("OR", other)
+    }
+}
+
+impl std::ops::Not for Expr {
+    type Output = Expr;
+
+    /// `!expr`: `NOT (a)`
+    fn not(mut self) -> Self {
+        self.parts.insert(0, Part::Sql("NOT (".to_owned()));
+        self.parts.push(Part::Sql(")".to_owned()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cond, Dialect};
+
+    #[test]
+    fn expressions_build_sql() {
+        let age = Col::<i32>::new("\"age\"");
+        let name = Col::<String>::new("\"name\"");
+        let cond: Cond = age.gt(18).and(name.ilike("%a%")).or(!name.is_null()).into();
+        assert_eq!(
+            cond.build_for(Dialect::Postgres),
+            "(((\"age\" > $1 AND \"name\" ILIKE $2) OR NOT (\"name\" IS NULL)))"
+        );
+        assert_eq!(
+            cond.build_for(Dialect::Sqlite),
+            "(((\"age\" > ?1 AND \"name\" LIKE ?2) OR NOT (\"name\" IS NULL)))"
+        );
+        assert_eq!(cond.args, [Val::I32(18), Val::Str("%a%".to_owned())]);
+
+        let cond = Cond::new()
+            .eq("id", 1)
+            .filter(age.in_list([1, 2]))
+            .filter(name.in_list(Vec::<&str>::new()));
+        assert_eq!(
+            cond.build_for(Dialect::MySql),
+            "(id = ?) AND (\"age\" IN (?,?)) AND (1 = 0)"
+        );
+        assert_eq!(cond.args.len(), 3);
+    }
+}
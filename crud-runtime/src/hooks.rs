@@ -0,0 +1,48 @@
+use crate::CrudError;
+use std::future::Future;
+
+/// 写入前后的回调, 在结构体上标记 `#[crud(hooks)]` 后由生成的方法调用; 默认不做任何事
+///
+/// - `create` / `create_returning` / `create_returning_id` / `create_or_skip_by`: `before_create` / `after_create`
+/// - `save` / `update`: `before_save` / `after_save`
+/// - `delete` / `force_delete`: `before_delete` / `after_delete`
+///
+/// `before_*` 返回 Err 时取消操作并返回该错误 (可以使用 `CrudError::Rejected`).
+/// `before_create` / `before_save` 可以修改记录, 写入的是修改后的副本, 调用方的值不变.
+/// `update` 只写入 `cond_fields`: `before_save` 只能取消操作, 修改不会写入; `after_save` 收到的是调用方的值
+/// (不包括 `cond_fields` 的修改和新的版本号).
+/// `after_*` 在写入成功后调用, 返回的错误会作为方法的结果, 但不会撤销已经完成的写入 (需要时在事务中调用).
+///
+/// ```ignore
+/// impl CrudHooks for User {
+///     async fn before_create(&mut self) -> Result<(), CrudError> {
+///         self.email = self.email.trim().to_lowercase();
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait CrudHooks: Clone + Send + Sync {
+    fn before_create(&mut self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn after_create(&self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn before_save(&mut self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn after_save(&self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn before_delete(&self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn after_delete(&self) -> impl Future<Output = Result<(), CrudError>> + Send {
+        async { Ok(()) }
+    }
+}
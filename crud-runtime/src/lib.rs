@@ -0,0 +1,48 @@
+//! crud-derive 生成代码使用的运行时类型
+
+mod bind;
+mod cond;
+mod copy;
+mod cursor;
+mod ddl;
+mod dialect;
+mod dt;
+mod error;
+mod expr;
+mod hooks;
+mod schema;
+mod types;
+mod upsert;
+mod val;
+mod validate;
+
+pub use bind::BindVal;
+pub use cond::{order_by_sql, Cond};
+pub use copy::CopyValue;
+pub use cursor::{keyset_limit, keyset_order, keyset_sql, Cursor, Page, MAX_PAGE_LIMIT};
+pub use ddl::{Migrations, SqlType};
+pub use dialect::Dialect;
+pub use dt::now_utc_micro;
+pub use error::CrudError;
+pub use expr::{Col, ColValue, Expr};
+pub use hooks::CrudHooks;
+pub use schema::{check_schema, ColumnInfo, ExpectedColumn, SchemaIssue, SchemaReport};
+pub use types::{Db, IdName, Total};
+pub use upsert::UpsertUpdate;
+pub use val::Val;
+pub use validate::{ValidateLength, ValidateRange, ValidateStr, ValidationError, ValidationErrors};
+
+#[doc(hidden)]
+pub use async_stream;
+#[doc(hidden)]
+pub use futures_core;
+#[doc(hidden)]
+pub use log;
+#[doc(hidden)]
+pub use regex;
+pub use rust_i18n::set_locale;
+
+#[macro_use]
+extern crate rust_i18n;
+
+i18n!("locales", fallback = "en");
@@ -0,0 +1,312 @@
+use crate::Dialect;
+use std::fmt;
+
+/// 结构体期望的列
+#[derive(Debug, Clone)]
+pub struct ExpectedColumn {
+    /// 列名 (不带引号)
+    pub name: &'static str,
+    /// 列类型: `SqlType` 或 `#[crud(sql_type = "...")]`
+    pub sql_type: &'static str,
+    /// 字段是否为 `Option`
+    pub nullable: bool,
+}
+
+/// 数据库中的列
+#[derive(Debug, Clone)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    /// 有默认值、自增或由数据库生成
+    pub has_default: bool,
+}
+
+impl From<(String, String, String, Option<String>, String)> for ColumnInfo {
+    /// (列名, 类型, 是否可以为 NULL (`YES` / `NO`), 默认值, 自增 / 生成列的标记)
+    fn from(row: (String, String, String, Option<String>, String)) -> Self {
+        let (name, data_type, nullable, default, extra) = row;
+        let extra = extra.to_ascii_lowercase();
+        Self {
+            name,
+            data_type,
+            nullable: nullable.eq_ignore_ascii_case("YES"),
+            has_default: default.is_some()
+                || extra.contains("auto_increment")
+                || extra.contains("generated"),
+        }
+    }
+}
+
+/// 表结构的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// 表不存在
+    MissingTable,
+    /// 缺少字段对应的列
+    MissingColumn { column: String },
+    /// 列类型与字段类型不符
+    TypeMismatch {
+        column: String,
+        expected: String,
+        actual: String,
+    },
+    /// 列可以为 NULL, 但字段不是 `Option`
+    Nullable { column: String },
+    /// 结构体中没有的 NOT NULL 列且没有默认值, 写入时会失败
+    ExtraRequired { column: String },
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaIssue::MissingTable => write!(f, "表不存在"),
+            SchemaIssue::MissingColumn { column } => write!(f, "缺少列 {}", column),
+            SchemaIssue::TypeMismatch {
+                column,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "列 {} 类型不符: 字段为 {}, 数据库为 {}",
+                column, expected, actual
+            ),
+            SchemaIssue::Nullable { column } => {
+                write!(f, "列 {} 可以为 NULL, 字段应为 Option", column)
+            }
+            SchemaIssue::ExtraRequired { column } => write!(
+                f,
+                "列 {} 为 NOT NULL 且没有默认值, 但结构体中没有对应的字段",
+                column
+            ),
+        }
+    }
+}
+
+/// `verify_schema` 的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaReport {
+    pub table: String,
+    pub issues: Vec<SchemaIssue>,
+}
+
+impl SchemaReport {
+    /// 没有问题
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "表 {} 的结构与结构体不一致:", self.table)?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// 比较期望的列与数据库中的列
+pub fn check_schema(
+    dialect: Dialect,
+    table: &str,
+    expected: &[ExpectedColumn],
+    actual: &[ColumnInfo],
+) -> SchemaReport {
+    let mut issues = vec![];
+    if actual.is_empty() {
+        issues.push(SchemaIssue::MissingTable);
+    }
+    // PostgreSQL 的列名区分大小写
+    let same = |a: &str, b: &str| match dialect {
+        Dialect::Postgres => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    };
+    for column in expected.iter().filter(|_| !actual.is_empty()) {
+        let Some(info) = actual.iter().find(|c| same(&c.name, column.name)) else {
+            issues.push(SchemaIssue::MissingColumn {
+                column: column.name.to_owned(),
+            });
+            continue;
+        };
+        if normalize_type(dialect, column.sql_type) != normalize_type(dialect, &info.data_type) {
+            issues.push(SchemaIssue::TypeMismatch {
+                column: column.name.to_owned(),
+                expected: column.sql_type.to_owned(),
+                actual: info.data_type.clone(),
+            });
+        }
+        if info.nullable && !column.nullable {
+            issues.push(SchemaIssue::Nullable {
+                column: column.name.to_owned(),
+            });
+        }
+    }
+    for info in actual {
+        if !info.nullable && !info.has_default && !expected.iter().any(|c| same(c.name, &info.name))
+        {
+            issues.push(SchemaIssue::ExtraRequired {
+                column: info.name.clone(),
+            });
+        }
+    }
+    SchemaReport {
+        table: table.to_owned(),
+        issues,
+    }
+}
+
+/// 统一类型名称以便比较: 去掉长度等参数, 别名转换为同一个名称
+fn normalize_type(dialect: Dialect, name: &str) -> String {
+    let mut lower = name.to_ascii_lowercase();
+    while let (Some(start), Some(end)) = (lower.find('('), lower.find(')')) {
+        if end < start {
+            break;
+        }
+        lower.replace_range(start..=end, "");
+    }
+    let lower = lower.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = match dialect {
+        Dialect::Postgres => match lower.as_str() {
+            "int2" | "smallserial" => "smallint",
+            "int" | "int4" | "serial" => "integer",
+            "int8" | "bigserial" => "bigint",
+            "float4" => "real",
+            "float8" | "double precision" => "double",
+            "bool" => "boolean",
+            "varchar" | "character varying" | "char" | "character" | "bpchar" | "name"
+            | "citext" => "text",
+            "timestamp without time zone" => "timestamp",
+            "timestamp with time zone" => "timestamptz",
+            "time without time zone" => "time",
+            "decimal" => "numeric",
+            other => other,
+        },
+        Dialect::MySql => match lower.as_str() {
+            "integer" => "int",
+            "integer unsigned" => "int unsigned",
+            "bool" | "boolean" => "tinyint",
+            "varchar" | "char" | "tinytext" | "mediumtext" | "longtext" => "text",
+            "varbinary" | "binary" | "tinyblob" | "mediumblob" | "longblob" => "blob",
+            "real" | "double precision" => "double",
+            "numeric" => "decimal",
+            other => other,
+        },
+        // SQLite 只比较类型亲和性
+        Dialect::Sqlite => {
+            if lower.contains("int") {
+                "integer"
+            } else if ["char", "clob", "text"].iter().any(|t| lower.contains(t)) {
+                "text"
+            } else if lower.is_empty() || lower.contains("blob") {
+                "blob"
+            } else if ["real", "floa", "doub"].iter().any(|t| lower.contains(t)) {
+                "real"
+            } else {
+                "numeric"
+            }
+        }
+    };
+    name.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(name: &str, data_type: &str, nullable: bool, has_default: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_owned(),
+            data_type: data_type.to_owned(),
+            nullable,
+            has_default,
+        }
+    }
+
+    #[test]
+    fn types_are_normalized() {
+        assert_eq!(
+            normalize_type(Dialect::Postgres, "character varying(255)"),
+            normalize_type(Dialect::Postgres, "TEXT")
+        );
+        assert_eq!(normalize_type(Dialect::Postgres, "int8"), "bigint");
+        assert_eq!(
+            normalize_type(Dialect::Postgres, "timestamp with time zone"),
+            "timestamptz"
+        );
+        assert_eq!(
+            normalize_type(Dialect::Postgres, "numeric(10, 2)"),
+            "numeric"
+        );
+        assert_eq!(
+            normalize_type(Dialect::MySql, "INT(11) UNSIGNED"),
+            "int unsigned"
+        );
+        assert_eq!(normalize_type(Dialect::MySql, "tinyint(1)"), "tinyint");
+        assert_eq!(normalize_type(Dialect::Sqlite, "BIGINT"), "integer");
+        assert_eq!(normalize_type(Dialect::Sqlite, "VARCHAR(10)"), "text");
+        assert_eq!(normalize_type(Dialect::Sqlite, "DOUBLE"), "real");
+        assert_eq!(normalize_type(Dialect::Sqlite, ""), "blob");
+        assert_ne!(
+            normalize_type(Dialect::Postgres, "integer"),
+            normalize_type(Dialect::Postgres, "bigint")
+        );
+    }
+
+    #[test]
+    fn schema_issues() {
+        let expected = [
+            ExpectedColumn {
+                name: "id",
+                sql_type: "BIGINT",
+                nullable: false,
+            },
+            ExpectedColumn {
+                name: "name",
+                sql_type: "TEXT",
+                nullable: false,
+            },
+            ExpectedColumn {
+                name: "email",
+                sql_type: "TEXT",
+                nullable: true,
+            },
+        ];
+        let actual = [
+            info("id", "integer", false, true),
+            info("name", "varchar(20)", true, false),
+            info("age", "integer", false, false),
+            info("created_at", "timestamp", false, true),
+        ];
+        let report = check_schema(Dialect::Postgres, "users", &expected, &actual);
+        assert_eq!(
+            report.issues,
+            vec![
+                SchemaIssue::TypeMismatch {
+                    column: "id".to_owned(),
+                    expected: "BIGINT".to_owned(),
+                    actual: "integer".to_owned(),
+                },
+                SchemaIssue::Nullable {
+                    column: "name".to_owned(),
+                },
+                SchemaIssue::MissingColumn {
+                    column: "email".to_owned(),
+                },
+                SchemaIssue::ExtraRequired {
+                    column: "age".to_owned(),
+                },
+            ]
+        );
+        assert!(!report.is_ok());
+
+        // MySQL / SQLite 的列名不区分大小写
+        let actual = [info("ID", "bigint(20)", false, true)];
+        assert!(check_schema(Dialect::MySql, "t", &expected[..1], &actual).is_ok());
+        assert_eq!(
+            check_schema(Dialect::Postgres, "t", &expected, &[]).issues,
+            vec![SchemaIssue::MissingTable]
+        );
+    }
+}
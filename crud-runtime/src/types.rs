@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{ColumnIndex, Decode, FromRow, Row, Type};
+
+/// 数据库连接池, 同时启用多个数据库时优先 postgres > mysql > sqlite
+#[cfg(feature = "postgres")]
+pub type Db = sqlx::PgPool;
+#[cfg(all(feature = "mysql", not(feature = "postgres")))]
+pub type Db = sqlx::MySqlPool;
+#[cfg(all(feature = "sqlite", not(any(feature = "postgres", feature = "mysql"))))]
+pub type Db = sqlx::SqlitePool;
+#[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+compile_error!("crud-runtime 需要启用 postgres、mysql 或 sqlite 中的一个 feature");
+
+/// id 和 name, 用于下拉列表等场景
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdName {
+    pub id: i64,
+    pub name: String,
+}
+
+impl<'r, R> FromRow<'r, R> for IdName
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    i64: Decode<'r, R::Database> + Type<R::Database>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+        })
+    }
+}
+
+/// 统计结果: `SELECT COUNT(*) AS total`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Total {
+    pub total: i64,
+}
+
+impl<'r, R> FromRow<'r, R> for Total
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    i64: Decode<'r, R::Database> + Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            total: row.try_get("total")?,
+        })
+    }
+}
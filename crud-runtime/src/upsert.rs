@@ -0,0 +1,10 @@
+/// upsert 发生冲突时的更新方式, `C` 为生成的列枚举 (例如 `UserColumn`)
+#[derive(Debug, Clone)]
+pub enum UpsertUpdate<C> {
+    /// 更新冲突列以外的全部可写列 (`created` 除外), 使用新插入的值
+    All,
+    /// 只更新指定的列, 使用新插入的值
+    Columns(Vec<C>),
+    /// 自定义表达式 `列 = 表达式`; PostgreSQL / SQLite 用 `EXCLUDED.列` 引用新值, MySQL 用 `VALUES(列)`
+    Exprs(Vec<(C, String)>),
+}
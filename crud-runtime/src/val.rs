@@ -0,0 +1,182 @@
+/// 带时区的时间: 启用 chrono 时为 `chrono::DateTime<Utc>`, 只启用 time 时为 `time::OffsetDateTime`
+#[cfg(feature = "chrono")]
+type Timestamp = chrono::DateTime<chrono::Utc>;
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+type Timestamp = time::OffsetDateTime;
+
+/// 日期: 启用 chrono 时为 `chrono::NaiveDate`, 只启用 time 时为 `time::Date`
+#[cfg(feature = "chrono")]
+type Date = chrono::NaiveDate;
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+type Date = time::Date;
+
+/// 查询条件 / 更新字段的值
+///
+/// `Uuid` / `Timestamp` / `Date` / `Decimal` / `Json` 需要启用对应的 feature
+/// (`uuid`、`chrono` 或 `time`、`rust_decimal`、`serde_json`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Val {
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Str(String),
+    S(&'static str),
+    Bool(bool),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    Timestamp(Timestamp),
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    Date(Date),
+    /// SQLite 不支持
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+    #[cfg(feature = "serde_json")]
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+    /// 数组, 只有 PostgreSQL 支持: `id = ANY($1)`
+    Array(Vec<Val>),
+    /// 由数据库推断类型的 NULL, 用于更新字段; 条件中应使用 `IS NULL`
+    Null,
+}
+
+impl Val {
+    /// `Val::Array`
+    pub fn array<V: Into<Val>>(vals: impl IntoIterator<Item = V>) -> Self {
+        Val::Array(vals.into_iter().map(Into::into).collect())
+    }
+}
+
+macro_rules! impl_from {
+    ($($(#[$meta:meta])* $t:ty => $v:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            impl From<$t> for Val {
+                fn from(v: $t) -> Self {
+                    Val::$v(v)
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    i8 => I8,
+    u8 => U8,
+    i16 => I16,
+    u16 => U16,
+    i32 => I32,
+    u32 => U32,
+    i64 => I64,
+    u64 => U64,
+    f32 => F32,
+    f64 => F64,
+    String => Str,
+    &'static str => S,
+    bool => Bool,
+    #[cfg(feature = "uuid")]
+    uuid::Uuid => Uuid,
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    Date => Date,
+    #[cfg(feature = "rust_decimal")]
+    rust_decimal::Decimal => Decimal,
+    #[cfg(feature = "serde_json")]
+    serde_json::Value => Json,
+    Vec<u8> => Bytes,
+}
+
+/// None 为 `Val::Null`
+impl<T: Into<Val>> From<Option<T>> for Val {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Val::Null, Into::into)
+    }
+}
+
+/// 转换为 UTC
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Val {
+    fn from(v: chrono::DateTime<Tz>) -> Self {
+        Val::Timestamp(v.with_timezone(&chrono::Utc))
+    }
+}
+
+/// 不带时区的时间按 UTC 处理
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for Val {
+    fn from(v: chrono::NaiveDateTime) -> Self {
+        Val::Timestamp(v.and_utc())
+    }
+}
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+impl From<time::OffsetDateTime> for Val {
+    fn from(v: time::OffsetDateTime) -> Self {
+        Val::Timestamp(v)
+    }
+}
+
+/// 不带时区的时间按 UTC 处理
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+impl From<time::PrimitiveDateTime> for Val {
+    fn from(v: time::PrimitiveDateTime) -> Self {
+        Val::Timestamp(v.assume_utc())
+    }
+}
+
+/// 同时启用 chrono 和 time 时, time 的值转换为 chrono; 超出 chrono 的范围 (time 的 `large-dates`) 时返回错误
+#[cfg(all(feature = "time", feature = "chrono"))]
+impl TryFrom<time::OffsetDateTime> for Val {
+    type Error = crate::CrudError;
+
+    fn try_from(v: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        chrono::DateTime::from_timestamp(v.unix_timestamp(), v.nanosecond())
+            .map(Val::Timestamp)
+            .ok_or_else(|| crate::CrudError::Validation(format!("时间超出范围: {}", v)))
+    }
+}
+
+#[cfg(all(feature = "time", feature = "chrono"))]
+impl TryFrom<time::PrimitiveDateTime> for Val {
+    type Error = crate::CrudError;
+
+    fn try_from(v: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+        v.assume_utc().try_into()
+    }
+}
+
+#[cfg(all(feature = "time", feature = "chrono"))]
+impl TryFrom<time::Date> for Val {
+    type Error = crate::CrudError;
+
+    fn try_from(v: time::Date) -> Result<Self, Self::Error> {
+        chrono::NaiveDate::from_ymd_opt(v.year(), u8::from(v.month()).into(), v.day().into())
+            .map(Val::Date)
+            .ok_or_else(|| crate::CrudError::Validation(format!("日期超出范围: {}", v)))
+    }
+}
+
+#[cfg(all(test, feature = "time", feature = "chrono"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_converts_to_chrono() {
+        let date = time::Date::from_calendar_date(2024, time::Month::January, 2).unwrap();
+        let t = date.with_hms_micro(3, 4, 5, 6).unwrap().assume_utc();
+        let v = Val::try_from(t).unwrap();
+        let expected = chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05.000006Z").unwrap();
+        assert_eq!(v, Val::from(expected));
+        assert_eq!(
+            Val::try_from(time::Date::from_calendar_date(2024, time::Month::February, 29).unwrap())
+                .unwrap(),
+            Val::Date(chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use regex::Regex;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// 可以校验长度的值 (字符串按字符计数), None 表示不校验
+pub trait ValidateLength {
+    fn validate_length(&self) -> Option<usize>;
+}
+
+impl ValidateLength for str {
+    fn validate_length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl ValidateLength for String {
+    fn validate_length(&self) -> Option<usize> {
+        self.as_str().validate_length()
+    }
+}
+
+impl<T> ValidateLength for Vec<T> {
+    fn validate_length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: ValidateLength + ?Sized> ValidateLength for &T {
+    fn validate_length(&self) -> Option<usize> {
+        (**self).validate_length()
+    }
+}
+
+impl<T: ValidateLength> ValidateLength for Option<T> {
+    fn validate_length(&self) -> Option<usize> {
+        self.as_ref().and_then(T::validate_length)
+    }
+}
+
+/// 可以校验格式 (email / regex) 的字符串, None 表示不校验
+pub trait ValidateStr {
+    fn validate_str(&self) -> Option<&str>;
+}
+
+impl ValidateStr for str {
+    fn validate_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl ValidateStr for String {
+    fn validate_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: ValidateStr + ?Sized> ValidateStr for &T {
+    fn validate_str(&self) -> Option<&str> {
+        (**self).validate_str()
+    }
+}
+
+impl<T: ValidateStr> ValidateStr for Option<T> {
+    fn validate_str(&self) -> Option<&str> {
+        self.as_ref().and_then(T::validate_str)
+    }
+}
+
+/// 可以校验范围的数值, None 表示不校验
+pub trait ValidateRange {
+    fn validate_range(&self) -> Option<f64>;
+}
+
+macro_rules! impl_validate_range {
+    ($($t:ty),*) => {
+        $(
+            impl ValidateRange for $t {
+                fn validate_range(&self) -> Option<f64> {
+                    Some(*self as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_validate_range!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+impl<T: ValidateRange + ?Sized> ValidateRange for &T {
+    fn validate_range(&self) -> Option<f64> {
+        (**self).validate_range()
+    }
+}
+
+impl<T: ValidateRange> ValidateRange for Option<T> {
+    fn validate_range(&self) -> Option<f64> {
+        self.as_ref().and_then(T::validate_range)
+    }
+}
+
+/// 一个字段的校验错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// 字段名称
+    pub field: &'static str,
+    /// 规则: `length` / `email` / `range` / `regex`
+    pub code: &'static str,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+impl ValidationError {
+    /// 当前语言 (`rust_i18n::set_locale`) 的错误信息
+    pub fn message(&self) -> String {
+        self.message_in(&rust_i18n::locale())
+    }
+
+    /// 指定语言的错误信息, 例如 `en` / `zh-CN`
+    pub fn message_in(&self, locale: &str) -> String {
+        let min = self.min.as_deref().unwrap_or_default();
+        let max = self.max.as_deref().unwrap_or_default();
+        let key = match (self.code, &self.min, &self.max) {
+            ("length", Some(_), Some(_)) => "validate.length_between",
+            ("length", Some(_), None) => "validate.length_min",
+            ("length", None, _) => "validate.length_max",
+            ("range", Some(_), Some(_)) => "validate.range_between",
+            ("range", Some(_), None) => "validate.range_min",
+            ("range", None, _) => "validate.range_max",
+            ("email", ..) => "validate.email",
+            _ => "validate.regex",
+        };
+        t!(key, locale = locale, min = min, max = max)
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message())
+    }
+}
+
+/// `validate()` 的结果: 全部字段的校验错误
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationErrors {
+    errors: Vec<ValidationError>,
+}
+
+fn email_regex() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    EMAIL.get_or_init(|| {
+        Regex::new(r"^[A-Za-z0-9.!#$%&'*+/=?^_`{|}~-]+@[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+$")
+            .expect("email regex")
+    })
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 全部错误, 按字段声明的顺序
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// 某个字段的错误
+    pub fn field_errors<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a ValidationError> {
+        self.errors.iter().filter(move |e| e.field == field)
+    }
+
+    fn push(
+        &mut self,
+        field: &'static str,
+        code: &'static str,
+        min: Option<String>,
+        max: Option<String>,
+    ) {
+        self.errors.push(ValidationError {
+            field,
+            code,
+            min,
+            max,
+        });
+    }
+
+    /// 长度在 [min, max] 之间
+    pub fn length<T: ValidateLength + ?Sized>(
+        &mut self,
+        field: &'static str,
+        value: &T,
+        min: Option<usize>,
+        max: Option<usize>,
+    ) {
+        if let Some(len) = value.validate_length() {
+            if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+                self.push(
+                    field,
+                    "length",
+                    min.map(|v| v.to_string()),
+                    max.map(|v| v.to_string()),
+                );
+            }
+        }
+    }
+
+    /// 邮箱地址
+    pub fn email<T: ValidateStr + ?Sized>(&mut self, field: &'static str, value: &T) {
+        if let Some(s) = value.validate_str() {
+            if !email_regex().is_match(s) {
+                self.push(field, "email", None, None);
+            }
+        }
+    }
+
+    /// 数值在 [min, max] 之间
+    pub fn range<T: ValidateRange + ?Sized>(
+        &mut self,
+        field: &'static str,
+        value: &T,
+        min: Option<f64>,
+        max: Option<f64>,
+    ) {
+        if let Some(v) = value.validate_range() {
+            if min.is_some_and(|min| v < min) || max.is_some_and(|max| v > max) {
+                self.push(
+                    field,
+                    "range",
+                    min.map(|v| v.to_string()),
+                    max.map(|v| v.to_string()),
+                );
+            }
+        }
+    }
+
+    /// 匹配正则表达式
+    pub fn regex<T: ValidateStr + ?Sized>(&mut self, field: &'static str, value: &T, re: &Regex) {
+        if let Some(s) = value.validate_str() {
+            if !re.is_match(s) {
+                self.push(field, "regex", None, None);
+            }
+        }
+    }
+
+    /// 没有错误时为 Ok
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_errors() {
+        let mut errors = ValidationErrors::new();
+        errors.length("name", "中文", Some(1), Some(2));
+        errors.length("name", &String::new(), Some(1), None);
+        errors.length("tags", &Some(vec![1, 2, 3]), None, Some(2));
+        errors.length("nick", &None::<String>, Some(1), None);
+        errors.email("email", "a@example.com");
+        errors.email("email", &Some("a@b".to_owned()));
+        errors.range("age", &18, Some(0.0), Some(150.0));
+        errors.range("age", &-1, Some(0.0), None);
+        errors.regex("code", "AB", &Regex::new("^[A-Z]+$").unwrap());
+        errors.regex("code", "ab", &Regex::new("^[A-Z]+$").unwrap());
+        let codes: Vec<_> = errors.errors().iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("name", "length"),
+                ("tags", "length"),
+                ("email", "email"),
+                ("age", "range"),
+                ("code", "regex"),
+            ]
+        );
+        assert_eq!(errors.field_errors("age").count(), 1);
+        assert_eq!(errors.field_errors("nick").count(), 0);
+        assert_eq!(
+            errors.errors()[0].message_in("en"),
+            "length must be at least 1"
+        );
+        assert_eq!(errors.errors()[3].message_in("en"), "must be at least 0");
+        assert!(errors.into_result().is_err());
+        assert_eq!(ValidationErrors::new().into_result(), Ok(()));
+    }
+}
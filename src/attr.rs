@@ -0,0 +1,407 @@
+//! `#[crud(...)]` 属性解析
+
+use crate::backend::Backend;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    Attribute, Expr, GenericArgument, Ident, LitInt, LitStr, Path, PathArguments, Type, Visibility,
+};
+
+/// 结构体属性: `#[crud(table = "...", schema = "...", crate = "...", backend = "...", soft_delete = "...", hooks)]`
+pub(crate) struct ContainerAttrs {
+    /// 表名, 未指定时使用结构体名称的蛇形复数
+    pub table: Option<String>,
+    /// 模式 (schema) 名称
+    pub schema: Option<String>,
+    /// 运行时 crate 路径, 默认 `::crud_runtime`
+    pub krate: Option<Path>,
+    /// 数据库类型
+    pub backend: Backend,
+    /// 软删除的列, 例如 `deleted_at`
+    pub soft_delete: Option<String>,
+    /// 一对多: `has_many(Order, foreign_key = "user_id")`
+    pub has_many: Vec<HasMany>,
+    /// 多对多: `many_to_many(Tag, join_table = "post_tags", foreign_key = "post_id", related_key = "tag_id")`
+    pub many_to_many: Vec<ManyToMany>,
+    /// 写入前后调用 `CrudHooks`
+    pub hooks: bool,
+}
+
+/// 一对多关系
+pub(crate) struct HasMany {
+    pub target: Path,
+    /// 对方表中指向本表主键的列, 默认 `本结构体蛇形_id`
+    pub foreign_key: Option<String>,
+}
+
+/// 多对多关系
+pub(crate) struct ManyToMany {
+    pub target: Path,
+    /// 中间表
+    pub join_table: String,
+    /// 中间表中指向本表主键的列, 默认 `本结构体蛇形_id`
+    pub foreign_key: Option<String>,
+    /// 中间表中指向对方主键的列, 默认 `对方结构体蛇形_id`
+    pub related_key: Option<String>,
+}
+
+impl ContainerAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self {
+            table: None,
+            schema: None,
+            krate: None,
+            backend: Backend::from_features(),
+            soft_delete: None,
+            has_many: vec![],
+            many_to_many: vec![],
+            hooks: false,
+        };
+        for attr in attrs.iter().filter(|a| a.path().is_ident("crud")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("table") {
+                    result.table = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("schema") {
+                    result.schema = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("crate") {
+                    result.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else if meta.path.is_ident("backend") {
+                    let name = meta.value()?.parse::<LitStr>()?;
+                    result.backend = Backend::from_name(&name.value()).ok_or_else(|| {
+                        syn::Error::new_spanned(&name, "backend 只支持 postgres / mysql / sqlite")
+                    })?;
+                } else if meta.path.is_ident("hooks") {
+                    result.hooks = true;
+                } else if meta.path.is_ident("soft_delete") {
+                    result.soft_delete = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("has_many") {
+                    let mut target = None;
+                    let mut foreign_key = None;
+                    meta.parse_nested_meta(|inner| {
+                        if inner.path.is_ident("foreign_key") {
+                            foreign_key = Some(inner.value()?.parse::<LitStr>()?.value());
+                        } else if target.is_none() {
+                            target = Some(inner.path.clone());
+                        } else {
+                            return Err(inner.error("不支持的 has_many 属性"));
+                        }
+                        Ok(())
+                    })?;
+                    let target = target.ok_or_else(|| meta.error("has_many 需要指定结构体"))?;
+                    result.has_many.push(HasMany {
+                        target,
+                        foreign_key,
+                    });
+                } else if meta.path.is_ident("many_to_many") {
+                    let mut target = None;
+                    let mut join_table = None;
+                    let mut foreign_key = None;
+                    let mut related_key = None;
+                    meta.parse_nested_meta(|inner| {
+                        if inner.path.is_ident("join_table") {
+                            join_table = Some(inner.value()?.parse::<LitStr>()?.value());
+                        } else if inner.path.is_ident("foreign_key") {
+                            foreign_key = Some(inner.value()?.parse::<LitStr>()?.value());
+                        } else if inner.path.is_ident("related_key") {
+                            related_key = Some(inner.value()?.parse::<LitStr>()?.value());
+                        } else if target.is_none() {
+                            target = Some(inner.path.clone());
+                        } else {
+                            return Err(inner.error("不支持的 many_to_many 属性"));
+                        }
+                        Ok(())
+                    })?;
+                    let target = target.ok_or_else(|| meta.error("many_to_many 需要指定结构体"))?;
+                    let join_table =
+                        join_table.ok_or_else(|| meta.error("many_to_many 需要指定 join_table"))?;
+                    result.many_to_many.push(ManyToMany {
+                        target,
+                        join_table,
+                        foreign_key,
+                        related_key,
+                    });
+                } else {
+                    return Err(meta.error("不支持的 crud 结构体属性"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(result)
+    }
+
+    /// 运行时 crate 路径
+    pub fn krate(&self) -> Path {
+        self.krate
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::crud_runtime))
+    }
+}
+
+/// 字段校验规则: `#[crud(validate(length(min = 1, max = 64), email, range(min = 0), regex = "..."))]`
+pub(crate) enum Rule {
+    Length {
+        min: Option<LitInt>,
+        max: Option<LitInt>,
+    },
+    Email,
+    Range {
+        min: Option<Box<Expr>>,
+        max: Option<Box<Expr>>,
+    },
+    Regex(LitStr),
+}
+
+impl Rule {
+    fn parse_list(meta: &ParseNestedMeta, rules: &mut Vec<Rule>) -> syn::Result<()> {
+        meta.parse_nested_meta(|rule| {
+            if rule.path.is_ident("email") {
+                rules.push(Rule::Email);
+            } else if rule.path.is_ident("regex") {
+                let lit = rule.value()?.parse::<LitStr>()?;
+                if let Err(err) = regex::Regex::new(&lit.value()) {
+                    return Err(syn::Error::new_spanned(&lit, err));
+                }
+                rules.push(Rule::Regex(lit));
+            } else if rule.path.is_ident("length") {
+                let (mut min, mut max) = (None, None);
+                rule.parse_nested_meta(|bound| {
+                    if bound.path.is_ident("min") {
+                        min = Some(bound.value()?.parse::<LitInt>()?);
+                    } else if bound.path.is_ident("max") {
+                        max = Some(bound.value()?.parse::<LitInt>()?);
+                    } else {
+                        return Err(bound.error("length 只支持 min / max"));
+                    }
+                    Ok(())
+                })?;
+                if min.is_none() && max.is_none() {
+                    return Err(rule.error("length 需要 min 或 max"));
+                }
+                rules.push(Rule::Length { min, max });
+            } else if rule.path.is_ident("range") {
+                let (mut min, mut max) = (None, None);
+                rule.parse_nested_meta(|bound| {
+                    if bound.path.is_ident("min") {
+                        min = Some(Box::new(bound.value()?.parse::<Expr>()?));
+                    } else if bound.path.is_ident("max") {
+                        max = Some(Box::new(bound.value()?.parse::<Expr>()?));
+                    } else {
+                        return Err(bound.error("range 只支持 min / max"));
+                    }
+                    Ok(())
+                })?;
+                if min.is_none() && max.is_none() {
+                    return Err(rule.error("range 需要 min 或 max"));
+                }
+                rules.push(Rule::Range { min, max });
+            } else {
+                return Err(rule.error("不支持的校验规则, 可用: length / email / range / regex"));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 字段信息: 字段本身 + `#[crud(column = "...", skip, readonly, primary_key, version, belongs_to = "...",
+/// unique, index, default = "...", sql_type = "...", validate(...))]`
+pub(crate) struct CrudField {
+    pub ident: Ident,
+    pub ty: Type,
+    pub vis: Visibility,
+    /// 字段名称 (去掉 `r#` 前缀)
+    pub name: String,
+    /// 数据库中的列名
+    pub column: String,
+    /// 不是数据库的列
+    pub skip: bool,
+    /// 只读: 可以查询, 但不参与 create / save
+    pub readonly: bool,
+    /// 主键 (可以标记多个字段组成联合主键)
+    pub primary_key: bool,
+    /// 乐观锁的版本号 (整数), save / update 时检查并加 1
+    pub version: bool,
+    /// 多对一: 该字段是指向另一个结构体主键的外键
+    pub belongs_to: Option<Path>,
+    /// 建表: 唯一约束
+    pub unique: bool,
+    /// 建表: 创建索引
+    pub index: bool,
+    /// 建表: 默认值 (SQL 表达式)
+    pub default: Option<String>,
+    /// 建表: 列类型, 不指定时由 `SqlType` 决定
+    pub sql_type: Option<String>,
+    /// create / save / upsert 之前的校验
+    pub validate: Vec<Rule>,
+    backend: Backend,
+}
+
+impl CrudField {
+    pub fn from_field(field: &syn::Field, backend: Backend) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("Expected named fields");
+        let name = ident.unraw().to_string();
+        let mut column = None;
+        let mut skip = false;
+        let mut readonly = false;
+        let mut primary_key = false;
+        let mut version = false;
+        let mut belongs_to = None;
+        let mut unique = false;
+        let mut index = false;
+        let mut default = None;
+        let mut sql_type = None;
+        let mut validate = vec![];
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("crud")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("column") {
+                    column = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("readonly") {
+                    readonly = true;
+                } else if meta.path.is_ident("primary_key") {
+                    primary_key = true;
+                } else if meta.path.is_ident("version") {
+                    version = true;
+                } else if meta.path.is_ident("belongs_to") {
+                    belongs_to = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+                } else if meta.path.is_ident("unique") {
+                    unique = true;
+                } else if meta.path.is_ident("index") {
+                    index = true;
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("sql_type") {
+                    sql_type = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("validate") {
+                    Rule::parse_list(&meta, &mut validate)?;
+                } else {
+                    return Err(meta.error("不支持的 crud 字段属性"));
+                }
+                Ok(())
+            })?;
+        }
+        if skip && primary_key {
+            return Err(syn::Error::new_spanned(&ident, "skip 字段不能作为主键"));
+        }
+        if version && (skip || readonly || primary_key) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "version 字段不能同时是 skip / readonly / primary_key",
+            ));
+        }
+        Ok(Self {
+            column: column.unwrap_or_else(|| name.clone()),
+            ident,
+            ty: field.ty.clone(),
+            vis: field.vis.clone(),
+            name,
+            skip,
+            readonly,
+            primary_key,
+            version,
+            belongs_to,
+            unique,
+            index,
+            default,
+            sql_type,
+            validate,
+            backend,
+        })
+    }
+
+    /// SQL 中使用的列名 (带引号)
+    pub fn sql_column(&self) -> String {
+        self.backend.quote_ident(&self.column)
+    }
+
+    /// SELECT 中使用的列: 列名与字段名不同时使用 `AS 字段名`, 以便 `FromRow` 按字段名读取
+    pub fn select_column(&self) -> String {
+        if self.column == self.name {
+            self.sql_column()
+        } else {
+            format!(
+                "{} AS {}",
+                self.sql_column(),
+                self.backend.quote_ident(&self.name)
+            )
+        }
+    }
+
+    /// 是否为 `Option<T>`: None 对应 NULL
+    pub fn is_option(&self) -> bool {
+        match &self.ty {
+            Type::Path(p) if p.qself.is_none() => p
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "Option"),
+            _ => false,
+        }
+    }
+
+    /// `Option<T>` 的 T, 其他类型为字段类型本身
+    pub fn value_type(&self) -> &Type {
+        if let Type::Path(p) = &self.ty {
+            if let Some(seg) = p.path.segments.last().filter(|_| self.is_option()) {
+                if let PathArguments::AngleBracketed(args) = &seg.arguments {
+                    if let Some(GenericArgument::Type(ty)) = args.args.first() {
+                        return ty;
+                    }
+                }
+            }
+        }
+        &self.ty
+    }
+
+    /// 是否为 Copy 类型: 整数、浮点数、布尔值、常用的 UUID / 时间 / Decimal 类型及其 `Option`
+    fn is_copy(&self) -> bool {
+        fn copy(ty: &Type) -> bool {
+            let Type::Path(p) = ty else {
+                return false;
+            };
+            let Some(seg) = p.path.segments.last().filter(|_| p.qself.is_none()) else {
+                return false;
+            };
+            match seg.ident.to_string().as_str() {
+                "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "f32" | "f64"
+                | "bool" | "char" | "Uuid" | "Decimal" | "DateTime" | "NaiveDate"
+                | "NaiveDateTime" | "NaiveTime" | "OffsetDateTime" | "PrimitiveDateTime" => true,
+                "Option" => match &seg.arguments {
+                    PathArguments::AngleBracketed(args) => {
+                        matches!(args.args.first(), Some(GenericArgument::Type(ty)) if copy(ty))
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        copy(&self.ty)
+    }
+
+    /// 绑定字段值的表达式: Copy 类型按值 `self.字段`, 其他类型按引用 `&self.字段`
+    pub fn bind_value(&self) -> proc_macro2::TokenStream {
+        let ident = &self.ident;
+        if self.is_copy() {
+            quote::quote!(self.#ident)
+        } else {
+            quote::quote!(&self.#ident)
+        }
+    }
+
+    /// 是否参与 create / save
+    pub fn writable(&self) -> bool {
+        !self.skip && !self.readonly
+    }
+
+    /// 由数据库生成的 id: 单一主键 `id` 且为整数 (SERIAL / AUTO_INCREMENT, 包括 MySQL 常用的 `u64` 等无符号整数), create 时不写入;
+    /// 其他 id (如 `Uuid` / `String` 主键) 由调用方提供
+    pub fn is_auto_id(&self, pk_fields: &[&CrudField]) -> bool {
+        let ty = &self.ty;
+        self.name == "id"
+            && matches!(pk_fields, [pk] if pk.ident == self.ident)
+            && matches!(
+                quote::quote!(#ty).to_string().as_str(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64"
+            )
+    }
+}
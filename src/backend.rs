@@ -0,0 +1,72 @@
+//! 数据库类型: 占位符、标识符引号等方言差异
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Path;
+
+/// 生成代码使用的数据库
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Backend {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl Backend {
+    /// 未指定 `#[crud(backend = "...")]` 时, 由 cargo feature 决定 (postgres > mysql > sqlite)
+    pub fn from_features() -> Self {
+        if cfg!(feature = "postgres") {
+            Backend::Postgres
+        } else if cfg!(feature = "mysql") {
+            Backend::MySql
+        } else if cfg!(feature = "sqlite") {
+            Backend::Sqlite
+        } else {
+            Backend::Postgres
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "postgres" | "pg" => Some(Backend::Postgres),
+            "mysql" => Some(Backend::MySql),
+            "sqlite" => Some(Backend::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// sqlx 的数据库类型
+    pub fn db_type(self) -> TokenStream {
+        match self {
+            Backend::Postgres => quote!(sqlx::Postgres),
+            Backend::MySql => quote!(sqlx::MySql),
+            Backend::Sqlite => quote!(sqlx::Sqlite),
+        }
+    }
+
+    /// 运行时的 `Dialect`
+    pub fn dialect(self, krate: &Path) -> TokenStream {
+        match self {
+            Backend::Postgres => quote!(#krate::Dialect::Postgres),
+            Backend::MySql => quote!(#krate::Dialect::MySql),
+            Backend::Sqlite => quote!(#krate::Dialect::Sqlite),
+        }
+    }
+
+    /// 第 index 个占位符 (从 1 开始); MySQL 只有按顺序的 `?`
+    pub fn placeholder(self, index: usize) -> String {
+        match self {
+            Backend::Postgres => format!("${}", index),
+            Backend::MySql => "?".to_owned(),
+            Backend::Sqlite => format!("?{}", index),
+        }
+    }
+
+    /// 标识符加引号, 列名可以是 `order` / `user` 等保留字
+    pub fn quote_ident(self, name: &str) -> String {
+        match self {
+            Backend::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+}
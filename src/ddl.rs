@@ -0,0 +1,174 @@
+//! 表结构: create_table_sql / verify_schema
+
+use crate::attr::CrudField;
+use crate::backend::Backend;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Path;
+
+/// 生成 `create_table_sql`; table 为不带 schema 和引号的表名, 用于索引名称
+pub(crate) fn expand_create_table(
+    backend: Backend,
+    krate: &Path,
+    table: &str,
+    fields: &[CrudField],
+    pk_fields: &[&CrudField],
+) -> TokenStream {
+    let dialect = backend.dialect(krate);
+    let mut columns = vec![];
+    let mut bounds = vec![];
+    let mut indexes = vec![];
+    for field in fields.iter().filter(|f| !f.skip) {
+        let column = field.sql_column();
+        let ty = &field.ty;
+        let single_pk = matches!(pk_fields, [pk] if pk.ident == field.ident);
+        // 主键 id 由数据库生成 (create 时不写入 id)
+        let ty_name = ty.to_token_stream().to_string();
+        if field.is_auto_id(pk_fields) {
+            let def = match (backend, ty_name.as_str()) {
+                (Backend::Postgres, "i16") => "SMALLSERIAL PRIMARY KEY",
+                (Backend::Postgres, "i32" | "u16") => "SERIAL PRIMARY KEY",
+                (Backend::Postgres, _) => "BIGSERIAL PRIMARY KEY",
+                (Backend::MySql, "i16") => "SMALLINT NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::MySql, "u16") => "SMALLINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::MySql, "i32") => "INT NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::MySql, "u32") => "INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::MySql, "u64") => "BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::MySql, _) => "BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                (Backend::Sqlite, _) => "INTEGER PRIMARY KEY AUTOINCREMENT",
+            };
+            let def = format!("{} {}", column, def);
+            columns.push(quote!(String::from(#def)));
+            continue;
+        }
+        let sql_type = match &field.sql_type {
+            Some(sql_type) => quote!(#sql_type),
+            None => {
+                bounds.push(quote!(for<'r> &'r #ty: #krate::SqlType));
+                quote!(<&#ty as #krate::SqlType>::sql_type(#dialect))
+            }
+        };
+        let mut suffix = String::new();
+        if !field.is_option() {
+            suffix.push_str(" NOT NULL");
+        }
+        if single_pk {
+            suffix.push_str(" PRIMARY KEY");
+        }
+        if field.unique {
+            suffix.push_str(" UNIQUE");
+        }
+        if let Some(default) = &field.default {
+            suffix.push_str(&format!(" DEFAULT {}", default));
+        }
+        columns.push(quote!(format!("{} {}{}", #column, #sql_type, #suffix)));
+        if field.index {
+            let name = backend.quote_ident(&format!("{}_{}_idx", table, field.column));
+            if backend == Backend::MySql {
+                let def = format!("INDEX {} ({})", name, column);
+                indexes.push(quote!(columns.push(String::from(#def));));
+            } else {
+                let def = format!(
+                    "\nCREATE INDEX IF NOT EXISTS {} ON {{}} ({});",
+                    name, column
+                );
+                indexes.push(quote!(after.push_str(&format!(#def, Self::get_table_name()));));
+            }
+        }
+    }
+    if pk_fields.len() > 1 {
+        let def = format!(
+            "PRIMARY KEY ({})",
+            pk_fields
+                .iter()
+                .map(|f| f.sql_column())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        columns.push(quote!(String::from(#def)));
+    }
+    quote! {
+        /// 建表语句 (`CREATE TABLE IF NOT EXISTS` 及索引): 类型由 `SqlType` 或 `#[crud(sql_type = "...")]` 决定, 非 `Option` 字段为 NOT NULL
+        pub fn create_table_sql() -> String
+        where
+            #(#bounds,)*
+        {
+            #[allow(unused_mut)]
+            let mut columns: Vec<String> = vec![#(#columns),*];
+            #[allow(unused_mut)]
+            let mut after = String::new();
+            #(#indexes)*
+            format!("CREATE TABLE IF NOT EXISTS {} (\n    {}\n);{}", Self::get_table_name(), columns.join(",\n    "), after)
+        }
+    }
+}
+
+/// 生成 `verify_schema`; schema / table 为不带引号的名称
+pub(crate) fn expand_verify_schema(
+    backend: Backend,
+    krate: &Path,
+    schema: Option<&str>,
+    table: &str,
+    fields: &[CrudField],
+) -> TokenStream {
+    let db = backend.db_type();
+    let dialect = backend.dialect(krate);
+    let mut bounds = vec![];
+    let mut expected = vec![];
+    for field in fields.iter().filter(|f| !f.skip) {
+        let ty = &field.ty;
+        let name = &field.column;
+        let nullable = field.is_option();
+        let sql_type = match &field.sql_type {
+            Some(sql_type) => quote!(#sql_type),
+            None => {
+                bounds.push(quote!(for<'r> &'r #ty: #krate::SqlType));
+                quote!(<&#ty as #krate::SqlType>::sql_type(#dialect))
+            }
+        };
+        expected.push(quote! {
+            #krate::ExpectedColumn { name: #name, sql_type: #sql_type, nullable: #nullable }
+        });
+    }
+    // (列名, 类型, 是否可以为 NULL, 默认值, 自增 / 生成列的标记), 参数为 schema (可以为 NULL) 和表名
+    let sql = match backend {
+        Backend::Postgres => "SELECT column_name::text, udt_name::text, is_nullable::text, column_default::text, \
+             CASE WHEN is_identity = 'YES' OR is_generated <> 'NEVER' THEN 'generated' ELSE '' END \
+             FROM information_schema.columns WHERE table_schema = COALESCE($1, current_schema()) AND table_name = $2",
+        Backend::MySql => "SELECT CAST(COLUMN_NAME AS CHAR), CAST(COLUMN_TYPE AS CHAR), CAST(IS_NULLABLE AS CHAR), \
+             CAST(COLUMN_DEFAULT AS CHAR), CAST(EXTRA AS CHAR) \
+             FROM information_schema.columns WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?",
+        Backend::Sqlite => "SELECT name, type, CASE WHEN \"notnull\" = 0 AND pk = 0 THEN 'YES' ELSE 'NO' END, dflt_value, \
+             CASE WHEN pk > 0 AND lower(type) = 'integer' THEN 'auto_increment' ELSE '' END \
+             FROM pragma_table_info(?2, COALESCE(?1, 'main'))",
+    };
+    let schema = match schema {
+        Some(schema) => quote!(Some(#schema)),
+        None => quote!(None::<&str>),
+    };
+    quote! {
+        /// 检查数据库中的表结构: 缺少的列、类型不符、可以为 NULL 但字段不是 Option、结构体中没有的必填列;
+        /// 不一致时返回 `CrudError::Schema`, 适合在启动时调用
+        pub async fn verify_schema<'e, E>(executor: E) -> Result<(), #krate::CrudError>
+        where
+            E: sqlx::Executor<'e, Database = #db>,
+            #(#bounds,)*
+        {
+            let expected = [#(#expected),*];
+            let actual: Vec<#krate::ColumnInfo> = sqlx::query_as::<_, (String, String, String, Option<String>, String)>(#sql)
+                .bind(#schema)
+                .bind(#table)
+                .fetch_all(executor)
+                .await?
+                .into_iter()
+                .map(#krate::ColumnInfo::from)
+                .collect();
+            let report = #krate::check_schema(#dialect, Self::get_table_name(), &expected, &actual);
+            if report.is_ok() {
+                Ok(())
+            } else {
+                Err(#krate::CrudError::Schema(report))
+            }
+        }
+    }
+}
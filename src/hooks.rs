@@ -0,0 +1,107 @@
+//! 写入前后调用 CrudHooks: `#[crud(hooks)]`
+
+use crate::backend::Backend;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, Path};
+
+/// 回调的种类
+#[derive(Clone, Copy)]
+pub(crate) enum Hook {
+    Create,
+    Save,
+    Delete,
+}
+
+/// 生成方法的可见性和名称: 启用 hooks 时原方法改为私有的 `raw_{name}`, 由 `expand_hooked` 生成同名的公开方法
+pub(crate) fn method_name(hooks: bool, name: &str) -> (TokenStream, Ident) {
+    if hooks {
+        (quote!(), format_ident!("raw_{}", name))
+    } else {
+        (quote!(pub), format_ident!("{}", name))
+    }
+}
+
+/// 公开的方法: 调用 `before_*`, 然后 `raw_{name}`, 成功后调用 `after_*`
+///
+/// extra 为 executor 之后的参数 (名称, 类型), ret 为 Ok 的类型
+pub(crate) fn expand_hooked(
+    backend: Backend,
+    krate: &Path,
+    hook: Hook,
+    name: &str,
+    doc: &str,
+    extra: &[(&str, TokenStream)],
+    ret: TokenStream,
+) -> TokenStream {
+    let db = backend.db_type();
+    let names: Vec<Ident> = extra.iter().map(|(n, _)| format_ident!("{}", n)).collect();
+    let types = extra.iter().map(|(_, ty)| ty);
+    let params = quote!(#(, #names: #types)*);
+    let args = quote!(#(, #names)*);
+    let method = format_ident!("{}", name);
+    let raw = format_ident!("raw_{}", name);
+    let (before, after) = match hook {
+        Hook::Create => (quote!(before_create), quote!(after_create)),
+        Hook::Save => (quote!(before_save), quote!(after_save)),
+        Hook::Delete => (quote!(before_delete), quote!(after_delete)),
+    };
+    // create / save 写入修改后的副本, delete 不需要复制
+    let row = match (hook, name) {
+        (Hook::Delete, _) => quote! {
+            let row = self;
+            #krate::CrudHooks::#before(row).await?;
+        },
+        // update 只写入 cond_fields: before_save 只能取消操作, 对副本的修改不会写入, 仍按调用方的主键 / 版本号定位记录
+        (_, "update") => quote! {
+            #krate::CrudHooks::#before(&mut self.clone()).await?;
+            let row = self;
+        },
+        _ => quote! {
+            let mut row = self.clone();
+            #krate::CrudHooks::#before(&mut row).await?;
+            let row = &row;
+        },
+    };
+    let after = match name {
+        // 冲突跳过时没有创建记录
+        "create_or_skip_by" => quote! {
+            if result {
+                #krate::CrudHooks::#after(row).await?;
+            }
+        },
+        // 返回的记录包括数据库生成的 id / 默认值
+        "create_returning" => quote!(#krate::CrudHooks::#after(&result).await?;),
+        _ => quote!(#krate::CrudHooks::#after(row).await?;),
+    };
+    if backend == Backend::MySql && name == "create_returning" {
+        // 原方法接收 Acquire: 在副本的生命周期内获取连接
+        return quote! {
+            #[doc = #doc]
+            pub fn #method<'a, A>(&'a self, db: A #params) -> impl std::future::Future<Output = Result<#ret, #krate::CrudError>> + Send + 'a
+            where
+                A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+            {
+                async move {
+                    #row
+                    let mut conn = db.acquire().await?;
+                    let result = row.#raw(&mut *conn #args).await?;
+                    #after
+                    Ok(result)
+                }
+            }
+        };
+    }
+    quote! {
+        #[doc = #doc]
+        pub async fn #method<'e, E>(&self, executor: E #params) -> Result<#ret, #krate::CrudError>
+        where
+            E: sqlx::Executor<'e, Database = #db>,
+        {
+            #row
+            let result = row.#raw(executor #args).await?;
+            #after
+            Ok(result)
+        }
+    }
+}
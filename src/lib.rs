@@ -1,17 +1,233 @@
 extern crate inflector;
 
-use inflector::cases::snakecase;
+mod attr;
+mod backend;
+mod ddl;
+mod hooks;
+mod relation;
+
+use attr::{ContainerAttrs, CrudField, Rule};
+use backend::Backend;
+use hooks::{expand_hooked, method_name, Hook};
+use inflector::cases::{pascalcase, snakecase};
 use inflector::string::pluralize;
 use proc_macro2::Ident;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
 /// 自动实现 crud
-#[proc_macro_derive(CRUDTable)]
+///
+/// 结构体属性: `#[crud(table = "...", schema = "...", crate = "...", backend = "...", soft_delete = "...", hooks)]`,
+/// 关联: `#[crud(has_many(Order, foreign_key = "..."), many_to_many(Tag, join_table = "...", foreign_key = "...", related_key = "..."))]`
+///
+/// 字段属性: `#[crud(column = "...", skip, readonly, primary_key, version, belongs_to = "User")]`,
+/// 建表: `#[crud(unique, index, default = "...", sql_type = "...")]`, 校验: `#[crud(validate(...))]`
+#[proc_macro_derive(CRUDTable, attributes(crud))]
 pub fn impl_crud_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input_clone = input.clone();
-    let input_clone2 = input.clone();
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(v) => v.into(), // 将代码转换为 token 流并返回
+        Err(err) => err.to_compile_error().into(),
+    }
+}
 
+/// 可以保存到游标中的字段类型
+fn is_cursor_type(ty: &syn::Type) -> bool {
+    matches!(
+        ty.to_token_stream().to_string().as_str(),
+        "i8" | "u8"
+            | "i16"
+            | "u16"
+            | "i32"
+            | "u32"
+            | "i64"
+            | "u64"
+            | "f32"
+            | "f64"
+            | "String"
+            | "bool"
+    )
+}
+
+/// `Some(v)` / `None`
+fn option_tokens(v: Option<proc_macro2::TokenStream>) -> proc_macro2::TokenStream {
+    match v {
+        Some(v) => quote!(Some(#v)),
+        None => quote!(None),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let container = ContainerAttrs::from_attrs(&input.attrs)?;
+    let krate = container.krate();
+    let backend = container.backend;
+    let db = backend.db_type();
+    let dialect = backend.dialect(&krate);
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => f
+                .named
+                .iter()
+                .map(|f| CrudField::from_field(f, backend))
+                .collect::<syn::Result<Vec<_>>>()?,
+            _ => return Err(syn::Error::new_spanned(&input, "Expected named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input, "Expected a struct")),
+    };
+    // id 字段及列名
+    let id_field = fields.iter().find(|f| f.name == "id" && !f.skip);
+    let id_column = id_field
+        .map(|f| f.sql_column())
+        .unwrap_or_else(|| backend.quote_ident("id"));
+    // 主键: 标记了 #[crud(primary_key)] 的字段, 未标记时使用 id 字段
+    let mut pk_fields: Vec<&CrudField> = fields.iter().filter(|f| f.primary_key).collect();
+    if pk_fields.is_empty() {
+        pk_fields.extend(id_field);
+    }
+    let pk_columns: Vec<String> = pk_fields.iter().map(|f| f.sql_column()).collect();
+    // 依据主键定位单条记录: `WHERE a = $n AND b = $n+1`, 占位符从 index 开始
+    let pk_where = quote! {
+        {
+            let mut wheres: Vec<String> = vec![];
+            #(
+                wheres.push(format!("{} = {}", #pk_columns, #dialect.placeholder(index)));
+                index += 1;
+            )*
+            wheres.join(" AND ")
+        }
+    };
+    let pk_self_binds: Vec<proc_macro2::TokenStream> = pk_fields
+        .iter()
+        .map(|f| {
+            let value = f.bind_value();
+            quote_spanned!(f.ty.span()=> builder = builder.bind(#value);)
+        })
+        .collect();
+    // find_by_pk 等方法的参数: 单个主键为该类型, 联合主键为元组
+    let (pk_type, pk_arg_binds) = if pk_fields.len() == 1 {
+        let ty = &pk_fields[0].ty;
+        (quote!(#ty), vec![quote!(builder = builder.bind(pk);)])
+    } else {
+        let tys = pk_fields.iter().map(|f| &f.ty);
+        let binds = (0..pk_fields.len())
+            .map(|i| {
+                let i = syn::Index::from(i);
+                quote!(builder = builder.bind(pk.#i);)
+            })
+            .collect();
+        (quote!((#(#tys),*)), binds)
+    };
+
+    // 乐观锁: save / update 时附加 `AND version = $n`, 没有更新到记录时返回 Conflict
+    let version_fields: Vec<&CrudField> = fields.iter().filter(|f| f.version).collect();
+    if let Some(extra) = version_fields.get(1) {
+        return Err(syn::Error::new_spanned(
+            &extra.ident,
+            "只能有一个 version 字段",
+        ));
+    }
+    let (version_where, version_bind, no_rows_error) = match version_fields.first() {
+        Some(f) => {
+            let column = f.sql_column();
+            let value = f.bind_value();
+            (
+                quote!(&format!(" AND {} = {}", #column, #dialect.placeholder(index))),
+                quote!(builder = builder.bind(#value);),
+                quote!(#krate::CrudError::Conflict),
+            )
+        }
+        None => (quote!(""), quote!(), quote!(#krate::CrudError::NotFound)),
+    };
+
+    // 软删除: 未删除 / 已删除的条件, 删除时 SET 的值 (运行时生成的 String), 恢复时 SET 的值
+    let soft_delete = container
+        .soft_delete
+        .as_ref()
+        .map(|name| {
+            let column = backend.quote_ident(name);
+            let field = fields
+                .iter()
+                .find(|f| !f.skip && &f.column == name)
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &input.ident,
+                        format!("soft_delete 的列 {} 没有对应的字段", name),
+                    )
+                })?;
+            let value_type = match field.value_type() {
+                syn::Type::Path(p) => p.path.segments.last().map(|seg| seg.ident.to_string()),
+                _ => None,
+            };
+            Ok(match (field.is_option(), value_type.as_deref()) {
+                // 与 created / updated 一致的微秒时间戳, 0 表示未删除
+                (false, Some("i64")) => (
+                    format!("{} = 0", column),
+                    format!("{} <> 0", column),
+                    quote!(format!("{} = {}", #column, #krate::now_utc_micro())),
+                    format!("{} = 0", column),
+                ),
+                (true, Some("i64")) => (
+                    format!("{} IS NULL", column),
+                    format!("{} IS NOT NULL", column),
+                    quote!(format!("{} = {}", #column, #krate::now_utc_micro())),
+                    format!("{} = NULL", column),
+                ),
+                (
+                    true,
+                    Some("DateTime" | "NaiveDateTime" | "OffsetDateTime" | "PrimitiveDateTime"),
+                ) => {
+                    let mark = format!("{} = CURRENT_TIMESTAMP", column);
+                    (
+                        format!("{} IS NULL", column),
+                        format!("{} IS NOT NULL", column),
+                        quote!(String::from(#mark)),
+                        format!("{} = NULL", column),
+                    )
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "soft_delete 字段只能是 i64、Option<i64> 或 Option<时间类型>",
+                    ))
+                }
+            })
+        })
+        .transpose()?;
+    // 查询时附加的过滤条件, 以及删除语句的开头 (软删除时为 UPDATE)
+    let (soft_where, soft_and, build_cond, delete_head) = match &soft_delete {
+        Some((alive, dead, mark, _)) => (
+            format!(" WHERE {}", alive),
+            format!(" AND {}", alive),
+            quote!(cond.build_soft_delete(#dialect, #alive, #dead)),
+            quote!(format!("UPDATE {} SET {}", Self::get_table_name(), #mark)),
+        ),
+        None => (
+            String::new(),
+            String::new(),
+            quote!(cond.build_for(#dialect)),
+            quote!(format!("DELETE FROM {}", Self::get_table_name())),
+        ),
+    };
+
+    let alive_cond = soft_delete
+        .as_ref()
+        .map(|(alive, ..)| alive.clone())
+        .unwrap_or_default();
+
+    // 列枚举: `User` -> `UserColumn`
+    let column_ident = format_ident!("{}Column", input.ident);
+
+    // #[crud(hooks)]: 写入方法改为 raw_*, 公开的同名方法在前后调用 CrudHooks
+    let hooks = container.hooks;
+    let (create_vis, create_fn) = method_name(hooks, "create");
+    let (skip_vis, skip_fn) = method_name(hooks, "create_or_skip_by");
+    let (returning_vis, returning_fn) = method_name(hooks, "create_returning");
+    let (returning_id_vis, returning_id_fn) = method_name(hooks, "create_returning_id");
+    let (update_vis, update_fn) = method_name(hooks, "update");
+    let (save_vis, save_fn) = method_name(hooks, "save");
+    let (delete_vis, delete_fn) = method_name(hooks, "delete");
+    let (force_delete_vis, force_delete_fn) = method_name(hooks, "force_delete");
     let mut tokens: Vec<proc_macro2::TokenStream> = Vec::new();
     tokens.push(quote! {
         pub fn new() -> Self {
@@ -19,10 +235,60 @@ pub fn impl_crud_table(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
         }
     });
     {
-        let DeriveInput { ident, data, .. } = parse_macro_input!(input);
-        let struct_type = ident.to_string();
-        let snake_case = snakecase::to_snake_case(&struct_type);
-        let table_name = pluralize::to_plural(&snake_case);
+        // 字段校验: 按字段声明的顺序收集全部错误
+        let mut checks = vec![];
+        for field in fields.iter() {
+            let ident = &field.ident;
+            let name = &field.name;
+            for rule in &field.validate {
+                checks.push(match rule {
+                    Rule::Length { min, max } => {
+                        let min = option_tokens(min.as_ref().map(|v| quote!(#v)));
+                        let max = option_tokens(max.as_ref().map(|v| quote!(#v)));
+                        quote_spanned!(field.ty.span()=> errors.length(#name, &self.#ident, #min, #max);)
+                    }
+                    Rule::Email => {
+                        quote_spanned!(field.ty.span()=> errors.email(#name, &self.#ident);)
+                    }
+                    Rule::Range { min, max } => {
+                        let min = option_tokens(min.as_ref().map(|v| quote!((#v) as f64)));
+                        let max = option_tokens(max.as_ref().map(|v| quote!((#v) as f64)));
+                        quote_spanned!(field.ty.span()=> errors.range(#name, &self.#ident, #min, #max);)
+                    }
+                    Rule::Regex(pattern) => quote_spanned! {field.ty.span()=>
+                        {
+                            static RE: std::sync::OnceLock<#krate::regex::Regex> = std::sync::OnceLock::new();
+                            let re = RE.get_or_init(|| #krate::regex::Regex::new(#pattern).unwrap());
+                            errors.regex(#name, &self.#ident, re);
+                        }
+                    },
+                });
+            }
+        }
+        tokens.push(quote! {
+            /// 按 `#[crud(validate(...))]` 校验字段, create / save / upsert 之前自动调用;
+            /// 错误信息的语言由 `set_locale` 决定
+            pub fn validate(&self) -> Result<(), #krate::ValidationErrors> {
+                #[allow(unused_mut)]
+                let mut errors = #krate::ValidationErrors::new();
+                #(#checks)*
+                errors.into_result()
+            }
+        });
+    }
+    let table = container.table.clone().unwrap_or_else(|| {
+        let snake_case = snakecase::to_snake_case(&input.ident.to_string());
+        pluralize::to_plural(&snake_case)
+    });
+    {
+        let table_name = match &container.schema {
+            Some(schema) => format!(
+                "{}.{}",
+                backend.quote_ident(schema),
+                backend.quote_ident(&table)
+            ),
+            None => backend.quote_ident(&table),
+        };
         tokens.push(quote! {
 
             #[inline]
@@ -30,482 +296,537 @@ pub fn impl_crud_table(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 #table_name
             }
 
+            /// 未被软删除的条件, 未启用软删除时为空
+            #[inline]
+            pub fn get_alive_cond() -> &'static str {
+                #alive_cond
+            }
+
+            /// `Cond::order_by` 的 SQL: 列只能是结构体的字段, 否则返回 `CrudError::Validation`
+            fn order_by_sql(cond: &#krate::Cond) -> Result<Option<String>, #krate::CrudError> {
+                cond.get_order_by()
+                    .map(|v| #krate::order_by_sql(v, |name| #column_ident::from_name(name).map(#column_ident::as_str)))
+                    .transpose()
+            }
+
             /// get_all: 获取全部记录
-            pub async fn get_all(pool: &common::types::Db) -> Result<Vec<Self>, &'static str> {
-                let sql = format!("SELECT {} FROM {}", Self::get_fields(), Self::get_table_name());
-                sqlx::query_as::<_, Self>(&sql).fetch_all(pool).await.map_err(|e| {
-                        println!("get_all error: {:?}", e);
-                        "获取数据失败"
-                })
-            }
-
-            /// get_all_by_cond: 获取带分页的全部记录
-            pub async fn get_all_by_cond(pool: &common::types::Db, cond: &common::types::Cond) -> Result<(Vec<Self>, i64), &'static str> {
-                let sql_cond = cond.build();
-                let where_str = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
-                let (page, page_size) = cond.get_limits();
-                let offset = (page - 1) * page_size;
-                let order_sort = if let Some(v) = cond.get_order_by() { format!("ORDER BY {}", v) } else { "".to_string() };
-                let sql = format!("SELECT {} FROM {} {} {} LIMIT {} OFFSET {}", Self::get_fields(), Self::get_table_name(), where_str, order_sort, page_size, offset);
-                log::info!("SQL: {}", &sql);
-                let where_str_total = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { "".to_string() };
-                let sql_total = format!("SELECT COUNT(*) AS total FROM {} {}", Self::get_table_name(), where_str_total);
-                log::info!("SQL TOTAL: {}", &sql_total);
-                let mut builder = sqlx::query_as::<_, Self>(&sql);
-                let mut builder_total = sqlx::query_as::<_, common::types::pg::Total>(&sql_total);
-                for v in &cond.args {
-                    match v {
-                        common::types::Val::I8(rv) =>   { builder = builder.bind::<i8>(*rv);            builder_total = builder_total.bind::<i8>(*rv); },
-                        common::types::Val::U8(rv) =>   { builder = builder.bind::<i8>(*rv as i8);      builder_total = builder_total.bind::<i8>(*rv as i8); },
-                        common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv);           builder_total = builder_total.bind::<i16>(*rv); },
-                        common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16);    builder_total = builder_total.bind::<i16>(*rv as i16); },
-                        common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv);           builder_total = builder_total.bind::<i32>(*rv); },
-                        common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32);    builder_total = builder_total.bind::<i32>(*rv as i32); },
-                        common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv);           builder_total = builder_total.bind::<i64>(*rv); },
-                        common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64);    builder_total = builder_total.bind::<i64>(*rv as i64); },
-                        common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv);           builder_total = builder_total.bind::<f32>(*rv); },
-                        common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv);           builder_total = builder_total.bind::<f64>(*rv); },
-                        common::types::Val::Str(rv) =>  { builder = builder.bind(rv);                   builder_total = builder_total.bind(rv); },
-                        common::types::Val::S(rv) =>    { builder = builder.bind(rv);                   builder_total = builder_total.bind(rv); },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv);                   builder_total = builder_total.bind(rv); },
-                            _ => { continue; }
+            pub async fn get_all<'e, E>(executor: E) -> Result<Vec<Self>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                let sql = format!("SELECT {} FROM {}{}", Self::get_fields(), Self::get_table_name(), #soft_where);
+                sqlx::query_as::<_, Self>(&sql).fetch_all(executor).await.map_err(#krate::CrudError::from)
+            }
+
+            /// get_all_by_cond: 获取带分页的全部记录 - 需要执行两次查询, 因此接收 `Acquire` (连接池 / 连接 / 事务)
+            pub fn get_all_by_cond<'a, A>(db: A, cond: &'a #krate::Cond) -> impl std::future::Future<Output = Result<(Vec<Self>, i64), #krate::CrudError>> + Send + 'a
+            where
+                A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+            {
+                async move {
+                    let mut conn = db.acquire().await?;
+                    cond.check()?;
+                    let sql_cond = #build_cond;
+                    let where_str = if !sql_cond.is_empty() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
+                    let (page, page_size) = cond.get_limits();
+                    let offset = (page - 1).saturating_mul(page_size);
+                    let order_sort = if let Some(v) = Self::order_by_sql(cond)? { format!("ORDER BY {}", v) } else { "".to_string() };
+                    let sql = format!("SELECT {} FROM {} {} {} LIMIT {} OFFSET {}", Self::get_fields(), Self::get_table_name(), where_str, order_sort, page_size, offset);
+                    #krate::log::info!("SQL: {}", &sql);
+                    let where_str_total = if !sql_cond.is_empty() { format!("WHERE {}", &sql_cond) } else { "".to_string() };
+                    let sql_total = format!("SELECT COUNT(*) AS total FROM {} {}", Self::get_table_name(), where_str_total);
+                    #krate::log::info!("SQL TOTAL: {}", &sql_total);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    let mut builder_total = sqlx::query_as::<_, #krate::Total>(&sql_total);
+                    builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                    builder_total = #krate::BindVal::bind_vals(builder_total, &cond.args)?;
+                    let rows = match builder.fetch_all(&mut *conn).await {
+                        Ok(v) => v,
+                        Err(err) => {
+                            #krate::log::error!("依据条侦探获取数据失败: {:?},\nSQL: {}", err, sql);
+                            return Err(err.into());
+                        }
                     };
+                    let rows_total = match builder_total.fetch_one(&mut *conn).await {
+                        Ok(v) => v,
+                        Err(err) => {
+                            #krate::log::error!("依据条件获取统计失败: {:?},\nSQL: {}", err, sql_total);
+                            return Err(err.into());
+                        }
+                    };
+                    Ok((rows, rows_total.total))
                 }
-                let rows = match builder.fetch_all(pool).await {
-                    Ok(v) => v,
-                    Err(err) => {
-                        log::error!("依据条侦探获取数据失败: {:?},\nSQL: {}", err, sql);
-                        return Err("获取数据失败: get_all_by_cond - select");
+            }
+
+            /// stream_all: 逐行读取全部记录, 不会一次载入内存
+            pub fn stream_all<'e, E>(executor: E) -> impl #krate::futures_core::Stream<Item = Result<Self, #krate::CrudError>> + Send + 'e
+            where
+                E: sqlx::Executor<'e, Database = #db> + 'e,
+            {
+                #krate::async_stream::try_stream! {
+                    let sql = format!("SELECT {} FROM {}{}", Self::get_fields(), Self::get_table_name(), #soft_where);
+                    let mut rows = sqlx::query_as::<_, Self>(&sql).fetch(executor);
+                    while let Some(row) = std::future::poll_fn(|cx| #krate::futures_core::Stream::poll_next(rows.as_mut(), cx)).await {
+                        yield row?;
                     }
-                };
-                let rows_total = match builder_total.fetch_one(pool).await {
-                    Ok(v) => v,
-                    Err(err) => {
-                        log::error!("依据条件获取统计失败: {:?},\nSQL: {}", err, sql_total);
-                        return Err("获取数据失败: get_all_by_cond - count");
+                }
+            }
+
+            /// stream_by_cond: 依据条件逐行读取记录 (忽略分页), 不会一次载入内存
+            pub fn stream_by_cond<'e, E>(executor: E, cond: &'e #krate::Cond) -> impl #krate::futures_core::Stream<Item = Result<Self, #krate::CrudError>> + Send + 'e
+            where
+                E: sqlx::Executor<'e, Database = #db> + 'e,
+            {
+                #krate::async_stream::try_stream! {
+                    cond.check()?;
+                    let sql_cond = #build_cond;
+                    let where_str = if !sql_cond.is_empty() { format!(" WHERE {}", &sql_cond) } else { String::new() };
+                    let order_sort = if let Some(v) = Self::order_by_sql(cond)? { format!(" ORDER BY {}", v) } else { String::new() };
+                    let sql = format!("SELECT {} FROM {}{}{}", Self::get_fields(), Self::get_table_name(), where_str, order_sort);
+                    #krate::log::info!("SQL: {}", &sql);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                    let mut rows = builder.fetch(executor);
+                    while let Some(row) = std::future::poll_fn(|cx| #krate::futures_core::Stream::poll_next(rows.as_mut(), cx)).await {
+                        yield row?;
                     }
-                };
-                // pool.close().await;
-                Ok((rows, rows_total.total))
+                }
             }
 
             /// count_by_cond 依据条件获取统计
-            pub async fn count_by_cond(pool: &common::types::Db, cond: &common::types::Cond) -> Result<i64, &'static str> {
-                let sql_cond = cond.build();
-                let where_str = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
+            pub async fn count_by_cond<'e, E>(executor: E, cond: &#krate::Cond) -> Result<i64, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                cond.check()?;
+                let sql_cond = #build_cond;
+                let where_str = if !sql_cond.is_empty() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
                 let sql = format!("SELECT COUNT(*) AS total FROM {} {}", Self::get_table_name(), where_str);
-                let mut builder = sqlx::query_as::<_, common::types::pg::Total>(&sql);
-                for v in &cond.args {
-                    match v {
-                        common::types::Val::I8(rv) =>   { builder = builder.bind::<i8>(*rv); },
-                        common::types::Val::U8(rv) =>   { builder = builder.bind::<i8>(*rv as i8); },
-                        common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv); },
-                        common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16); },
-                        common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv); },
-                        common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32); },
-                        common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv); },
-                        common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64); },
-                        common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv); },
-                        common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv); },
-                        common::types::Val::Str(rv) =>  { builder = builder.bind(rv); },
-                        common::types::Val::S(rv) =>    { builder = builder.bind(rv); },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv); },
-                        _ => { continue; }
-                    };
-                }
-                let rows = match builder.fetch_one(pool).await {
+                let mut builder = sqlx::query_as::<_, #krate::Total>(&sql);
+                builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                let rows = match builder.fetch_one(executor).await {
                     Ok(v) => v,
                     Err(err) => {
-                        log::error!("依据条件获取统计失败: {:?},\nSQL: {}", err, sql);
-                        return Err("获取数据失败: count_by_cond");
+                        #krate::log::error!("依据条件获取统计失败: {:?},\nSQL: {}", err, sql);
+                        return Err(err.into());
                     }
                 };
                 Ok(rows.total)
             }
 
             /// get_all_by_query: 获取按查询条件/分页的全部记录 - 可以把 Order by 写到 query 查询条件里面
-            pub async fn get_all_by_query(pool: &common::types::Db, query: &str, values: &[common::types::Val]) -> Result<Vec<Self>, &'static str> {
+            pub async fn get_all_by_query<'e, E>(executor: E, query: &str, values: &[#krate::Val]) -> Result<Vec<Self>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
                 let query_cond = if query.is_empty() { String::from("") } else { format!("WHERE {}", query) };
                 let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), &query_cond);
                 let mut builder = sqlx::query_as::<_, Self>(&sql);
-                for v in values {
-                    match v {
-                        common::types::Val::I8(rv)      => { builder = builder.bind::<i8>(*rv); },
-                        common::types::Val::U8(rv)      => { builder = builder.bind::<i8>(*rv as i8); },
-                        common::types::Val::I16(rv)     => { builder = builder.bind::<i16>(*rv); },
-                        common::types::Val::U16(rv)     => { builder = builder.bind::<i16>(*rv as i16); },
-                        common::types::Val::I32(rv)     => { builder = builder.bind::<i32>(*rv); },
-                        common::types::Val::U32(rv)     => { builder = builder.bind::<i32>(*rv as i32); },
-                        common::types::Val::I64(rv)     => { builder = builder.bind::<i64>(*rv); },
-                        common::types::Val::U64(rv)     => { builder = builder.bind::<i64>(*rv as i64); },
-                        common::types::Val::F32(rv)     => { builder = builder.bind::<f32>(*rv); },
-                        common::types::Val::F64(rv)     => { builder = builder.bind::<f64>(*rv); },
-                        common::types::Val::Str(rv)     => { builder = builder.bind(rv); },
-                        common::types::Val::S(rv)       => { builder = builder.bind(rv); },
-                        common::types::Val::Bool(rv)    => { builder = builder.bind(rv); },
-                        _ => { continue; }
-                    };
-                }
-                let rows = match builder.fetch_all(pool).await {
+                builder = #krate::BindVal::bind_vals(builder, values)?;
+                let rows = match builder.fetch_all(executor).await {
                     Ok(v) => v,
                     Err(err) => {
-                        log::error!("SQL: {}\n{}", sql, err);
-                        return Err("获取数据失败: 无法依据条件获取数据");
+                        #krate::log::error!("SQL: {}\n{}", sql, err);
+                        return Err(err.into());
                     }
                 };
                 Ok(rows)
             }
 
             /// get_all_by_query_raw: 获取全部记录
-            pub async fn get_all_by_query_raw(pool: &common::types::Db, sql: &str) -> Result<Vec<Self>, &'static str> {
-                sqlx::query_as::<_, Self>(sql).fetch_all(pool).await.map_err(|e| {
-                        log::error!("get_all error: {:?}", e);
-                        "获取数据失败"
-                })
+            pub async fn get_all_by_query_raw<'e, E>(executor: E, sql: &str) -> Result<Vec<Self>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                sqlx::query_as::<_, Self>(sql).fetch_all(executor).await.map_err(#krate::CrudError::from)
             }
 
-            /// get_by_cond: 查询单条记录 - 依据条件
-            pub async fn get_by_cond(pool: &common::types::Db, cond: &common::types::Cond) -> Option<Self> {
-                let sql_cond = cond.build();
-                let where_str = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { sql_cond };
+            /// get_by_cond: 查询单条记录 - 依据条件; 记录不存在时为 None, 条件无效 (`Validation`)、参数无法绑定 (`Overflow` / `Unsupported`) 或查询出错时记录错误日志并返回 None
+            pub async fn get_by_cond<'e, E>(executor: E, cond: &#krate::Cond) -> Option<Self>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                if let Err(err) = cond.check() {
+                    #krate::log::error!("查询条件无效: {:?}", err);
+                    return None;
+                }
+                let sql_cond = #build_cond;
+                let where_str = if !sql_cond.is_empty() { format!("WHERE {}", &sql_cond) } else { sql_cond };
                 let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), where_str);
-                if let Ok(v) = sqlx::query_as::<_, Self>(&sql).fetch_one(pool).await {
-                    return Some(v);
+                let mut builder = sqlx::query_as::<_, Self>(&sql);
+                builder = match #krate::BindVal::bind_vals(builder, &cond.args) {
+                    Ok(builder) => builder,
+                    Err(err) => {
+                        #krate::log::error!("绑定查询参数失败: {:?},\nSQL: {}", err, sql);
+                        return None;
+                    }
+                };
+                match builder.fetch_one(executor).await {
+                    Ok(v) => Some(v),
+                    Err(sqlx::Error::RowNotFound) => None,
+                    Err(err) => {
+                        #krate::log::error!("查询单条记录失败: {:?},\nSQL: {}", err, sql);
+                        None
+                    }
                 }
-                None
             }
 
-            /// get_by_query: 获取按查询条件/分页的单条记录 - 可以把 Order by 写到 query 查询条件里面
-            pub async fn get_by_query(pool: &common::types::Db, query: &str, values: &[common::types::Val]) -> Option<Self> {
-                if let Ok(mut rows) = Self::get_all_by_query(pool, query, values).await {
-                    return rows.pop();
+            /// get_by_query: 获取按查询条件/分页的单条记录 - 可以把 Order by 写到 query 查询条件里面; 出错时记录错误日志并返回 None
+            pub async fn get_by_query<'e, E>(executor: E, query: &str, values: &[#krate::Val]) -> Option<Self>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                match Self::get_all_by_query(executor, query, values).await {
+                    Ok(mut rows) => rows.pop(),
+                    Err(err) => {
+                        #krate::log::error!("查询单条记录失败: {:?}, 条件: {}", err, query);
+                        None
+                    }
                 }
-                None
             }
 
             /// get_by_query_raw: 查询单条记录 - 原始sql
-            pub async fn get_by_query_raw(pool: &common::types::Db, sql: &str) -> Option<Self> {
-                if let Ok(v) = sqlx::query_as::<_, Self>(sql).fetch_one(pool).await {
+            pub async fn get_by_query_raw<'e, E>(executor: E, sql: &str) -> Option<Self>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                if let Ok(v) = sqlx::query_as::<_, Self>(sql).fetch_one(executor).await {
                     return Some(v);
                 }
                 None
             }
         });
-        match data {
-            Data::Struct(s) => match s.fields {
-                Fields::Named(f) => {
-                    for field in f.named {
-                        let field_name = field.ident.as_ref().unwrap();
-                        let field_type = field.ty;
-
-                        // get_by_# 方法
-                        let current_field = format!("{}", field_name); // 当前字段名称
-                        let get_by_method = Ident::new(
-                            &format!("get_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 得到单条记录
-                            pub async fn #get_by_method(pool: &common::types::Db, field_value: &#field_type) -> Result<Self, &'static str> {
-                                let sql = format!("SELECT {} FROM {} WHERE {} = $1 LIMIT 1", Self::get_fields(), Self::get_table_name(), #current_field);
-                                sqlx::query_as::<_, Self>(&sql).bind(field_value).fetch_one(pool).await.map_err(|e| {
-                                        println!("{}", e);
-                                        "获取数据失败"
-                                    })
-                            }
-                        });
-
-                        // get_all_by_# 方法
-                        let get_all_by_method = Ident::new(
-                            &format!("get_all_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        let sql_where = format!("WHERE {} = $1", field_name.to_owned());
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 得到所有记录
-                            pub async fn #get_all_by_method(pool: &common::types::Db, field_value: &#field_type) -> Result<Vec<Self>, &'static str> {
-                                let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), #sql_where);
-                                sqlx::query_as::<_, Self>(&sql).bind(field_value).fetch_all(pool).await.map_err(|e| {
-                                        println!("{}", e);
-                                        "获取数据失败"
-                                    })
-                            }
-                        });
-
-                        // update_by
-                        let update_by_method = Ident::new(
-                            &format!("update_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        let update_where_sql = format!(
-                            "SET {} = $1 WHERE {} = $2 LIMIT 1",
-                            &field_name, &field_name
-                        );
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 更新单条记录
-                            pub async fn #update_by_method(pool: &common::types::Db, value_old: &#field_type, value_new: &#field_type) ->Result<(), &'static str> {
-                                let sql = format!("UPDATE {} {} LIMIT 1", Self::get_table_name(), #update_where_sql);
-                                match sqlx::query(&sql).bind(value_old).bind(value_new).execute(pool).await {
-                                        Ok(_) => Ok(()),
-                                        Err(err) => {
-                                            println!("{}", err);
-                                            Err("更新数据失败")
-                                        }
-                                }
-                            }
-                        });
-
-                        // update_all_by
-                        let update_all_by_method = Ident::new(
-                            &format!("update_all_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        let update_all_where_sql =
-                            format!("SET {} = $1 WHERE {} = $2", &field_name, &field_name);
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 更新单条记录
-                            pub async fn #update_all_by_method(pool: &common::types::Db, value_old: &#field_type, value_new: &#field_type) -> Result<(), &'static str> {
-                                let sql = format!("UPDATE {} {} LIMIT 1", Self::get_table_name(), #update_all_where_sql);
-                                match sqlx::query(&sql).bind(value_old).bind(value_new).execute(pool).await {
-                                        Ok(_) => Ok(()),
-                                        Err(err) => {
-                                            println!("{}", err);
-                                            Err("依据条件更新数据失败")
-                                        }
-                                }
-                            }
-                        });
-
-                        // delete_by
-                        let delete_by_method = Ident::new(
-                            &format!("delete_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        let delete_where_sql = format!("WHERE {} = $1", &field_name);
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 删除单条记录
-                            pub async fn #delete_by_method(pool: &common::types::Db, field_value: &#field_type) -> Result<(), &'static str> {
-                                let sql = format!("DELETE FROM {} {}", Self::get_table_name(), #delete_where_sql);
-                                match sqlx::query(&sql).bind(field_value).execute(pool).await {
-                                        Ok(_) => Ok(()),
-                                        Err(err) => {
-                                            println!("{}", err);
-                                            Err("依据条件删除数据失败")
-                                        }
-                                }
-                            }
-                        });
-
-                        // modify_by
-                        let modify_by_method = Ident::new(
-                            &format!("modify_by_{}", field_name),
-                            proc_macro2::Span::call_site(),
-                        );
-                        let modify_where_sql = format!("SET {} = $1", &field_name);
-                        tokens.push(quote!{
-                            /// 依据字段 #current_field 更新单条记录
-                            pub async fn #modify_by_method(&self, pool: &common::types::Db, field_value: &#field_type) -> Result<(), &'static str> {
-                                let change_sql = format!("UPDATE {} {} WHERE id = {}", Self::get_table_name(), #modify_where_sql, &self.id);
-                                match sqlx::query(&change_sql).bind(field_value).execute(pool).await {
-                                        Ok(_) => Ok(()),
-                                        Err(err) => {
-                                            println!("{}", err);
-                                            Err("依据条件更新数据失败")
-                                        }
-                                }
-                            }
-                        });
+        // update_by_* 通过子查询限制为一条记录, 没有主键时沿用 id 列
+        let (pk_key, pk_select) = match pk_columns.len() {
+            0 => (id_column.clone(), id_column.clone()),
+            1 => (pk_columns[0].clone(), pk_columns[0].clone()),
+            _ => (format!("({})", pk_columns.join(",")), pk_columns.join(",")),
+        };
+        for field in fields.iter().filter(|f| !f.skip) {
+            let field_name = &field.name;
+            let field_type = &field.ty;
+            let column = field.sql_column();
+            let p1 = backend.placeholder(1);
+            let p2 = backend.placeholder(2);
+            // 绑定字段值时使用字段类型的位置, 类型不能绑定时编译错误指向该字段
+            let ty_span = field.ty.span();
+            let bind_value = quote_spanned!(ty_span=> bind(field_value));
+
+            // get_by_# 方法
+            let current_field = column.to_owned(); // 当前字段列名
+            let get_by_method = Ident::new(
+                &format!("get_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            // Option 字段传入 None 时查询 `IS NULL`, 不绑定参数
+            let sql_where = format!("WHERE {} = {}{}", &column, &p1, &soft_and);
+            let (select_where, select_bind) = if field.is_option() {
+                let sql_where_null = format!("WHERE {} IS NULL{}", &column, &soft_and);
+                (
+                    quote! { let sql_where = if field_value.is_some() { #sql_where } else { #sql_where_null }; },
+                    quote! { if field_value.is_some() { builder = builder.#bind_value; } },
+                )
+            } else {
+                (
+                    quote! { let sql_where = #sql_where; },
+                    quote! { builder = builder.#bind_value; },
+                )
+            };
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 得到单条记录")]
+                pub async fn #get_by_method<'e, E>(executor: E, field_value: &#field_type) -> Result<Self, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    #select_where
+                    let sql = format!("SELECT {} FROM {} {} LIMIT 1", Self::get_fields(), Self::get_table_name(), sql_where);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    #select_bind
+                    builder.fetch_one(executor).await.map_err(#krate::CrudError::from)
+                }
+            });
+
+            // get_all_by_# 方法
+            let get_all_by_method = Ident::new(
+                &format!("get_all_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 得到所有记录")]
+                pub async fn #get_all_by_method<'e, E>(executor: E, field_value: &#field_type) -> Result<Vec<Self>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    #select_where
+                    let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), sql_where);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    #select_bind
+                    builder.fetch_all(executor).await.map_err(#krate::CrudError::from)
+                }
+            });
+
+            // delete_by
+            let delete_by_method = Ident::new(
+                &format!("delete_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            let delete_where_sql = format!("WHERE {} = {}{}", &column, &p1, &soft_and);
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 删除单条记录")]
+                pub async fn #delete_by_method<'e, E>(executor: E, field_value: &#field_type) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let sql = format!("{} {}", #delete_head, #delete_where_sql);
+                    sqlx::query(&sql).#bind_value.execute(executor).await?;
+                    Ok(())
+                }
+            });
+
+            // 只读字段不生成修改方法
+            if field.readonly {
+                continue;
+            }
+
+            // update_by
+            let update_by_method = Ident::new(
+                &format!("update_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            // MySQL 的 `?` 按顺序绑定, 且支持 UPDATE ... LIMIT
+            let (update_where_sql, update_all_where_sql, bind_first, bind_second) =
+                if backend == Backend::MySql {
+                    (
+                        format!("SET {} = ? WHERE {} = ? LIMIT 1", &column, &column),
+                        format!("SET {} = ? WHERE {} = ?", &column, &column),
+                        quote_spanned!(ty_span=> value_new),
+                        quote_spanned!(ty_span=> value_old),
+                    )
+                } else {
+                    (
+                        format!(
+                            "SET {} = {} WHERE {} IN (SELECT {} FROM {} WHERE {} = {} LIMIT 1)",
+                            &column, &p2, &pk_key, &pk_select, &table_name, &column, &p1
+                        ),
+                        format!("SET {} = {} WHERE {} = {}", &column, &p2, &column, &p1),
+                        quote_spanned!(ty_span=> value_old),
+                        quote_spanned!(ty_span=> value_new),
+                    )
+                };
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 更新单条记录")]
+                pub async fn #update_by_method<'e, E>(executor: E, value_old: &#field_type, value_new: &#field_type) ->Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let sql = format!("UPDATE {} {}", Self::get_table_name(), #update_where_sql);
+                    sqlx::query(&sql).bind(#bind_first).bind(#bind_second).execute(executor).await?;
+                    Ok(())
+                }
+            });
+
+            // update_all_by
+            let update_all_by_method = Ident::new(
+                &format!("update_all_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 更新所有记录")]
+                pub async fn #update_all_by_method<'e, E>(executor: E, value_old: &#field_type, value_new: &#field_type) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let sql = format!("UPDATE {} {}", Self::get_table_name(), #update_all_where_sql);
+                    sqlx::query(&sql).bind(#bind_first).bind(#bind_second).execute(executor).await?;
+                    Ok(())
+                }
+            });
+
+            // modify_by: 依据主键修改单个字段
+            if pk_fields.is_empty() {
+                continue;
+            }
+            let modify_by_method = Ident::new(
+                &format!("modify_by_{}", field_name),
+                proc_macro2::Span::call_site(),
+            );
+            let modify_set_sql = format!("SET {} = {} WHERE ", &column, &p1);
+            tokens.push(quote!{
+                #[doc = concat!("依据字段 ", #current_field, " 更新单条记录")]
+                pub async fn #modify_by_method<'e, E>(&self, executor: E, field_value: &#field_type) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 2;
+                    let where_sql = #pk_where;
+                    let change_sql = format!("UPDATE {} {}{}", Self::get_table_name(), #modify_set_sql, where_sql);
+                    let mut builder = sqlx::query(&change_sql).#bind_value;
+                    #(#pk_self_binds)*
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#krate::CrudError::NotFound);
                     }
+                    Ok(())
                 }
-                _ => panic!("Expected named fields"),
-            },
-            _ => panic!("Expected a struct"),
+            });
         }
     }
     {
         let mut table_fields: Vec<String> = vec![];
-        let DeriveInput { data, .. } = parse_macro_input!(input_clone);
-        let mut create_set_fields: Vec<syn::Stmt> = vec![]; // 创建记录时的字段处理
-        let mut create_builder_fields: Vec<syn::Stmt> = vec![]; // 创建记录时的 builder 处理
-        let mut update_set_fields: Vec<syn::Stmt> = vec![]; // 更新记录时的字段处理
-        let mut update_builder_fields: Vec<syn::Stmt> = vec![]; // 更新记录时的 builder 处理
-        let mut updated_set_fields: Vec<syn::Stmt> = vec![];
-        let mut updated_builder_fields: Vec<syn::Stmt> = vec![];
+        let mut create_set_fields: Vec<proc_macro2::TokenStream> = vec![]; // 创建记录时的字段处理
+        let mut create_builder_fields: Vec<proc_macro2::TokenStream> = vec![]; // 创建记录时的 builder 处理
+        let mut update_set_fields: Vec<proc_macro2::TokenStream> = vec![]; // 更新记录时的字段处理
+        let mut update_builder_fields: Vec<proc_macro2::TokenStream> = vec![]; // 更新记录时的 builder 处理
+        let mut updated_set_fields: Vec<proc_macro2::TokenStream> = vec![];
+        let mut updated_builder_fields: Vec<proc_macro2::TokenStream> = vec![];
+        let mut upsert_columns: Vec<String> = vec![]; // upsert 时默认更新的列
+        let mut upsert_version: Vec<proc_macro2::TokenStream> = vec![]; // upsert 更新时版本号加 1
+                                                                        // upsert 默认的冲突目标: 主键中由调用方写入的列, 数据库生成的 id 不会产生冲突
+        let upsert_pk_columns: Vec<String> = pk_fields
+            .iter()
+            .filter(|f| !f.is_auto_id(&pk_fields))
+            .map(|f| f.sql_column())
+            .collect();
         let mut has_id = false;
-        let mut has_name = false;
-
-        match data {
-            Data::Struct(s) => match s.fields {
-                Fields::Named(f) => {
-                    for field in f.named {
-                        let field_name = field.ident.unwrap().to_string();
-                        let field_type = field.ty.to_token_stream().to_string();
-
-                        table_fields.push(field_name.to_owned());
-                        if field_name == "id" {
-                            has_id = true;
-                            continue;
-                        }
-                        if field_name == "name" {
-                            has_name = true;
-                        }
-                        // 创建记录 - created 字段
-                        if field_name == "created" {
-                            let created_field = format!(
-                                r#"if self.created == 0 {{
-                                fields.push("created".to_owned());
-                                values.push(format!("${{}}", index));
-                                index += 1;
-                            }}"#
-                            );
-                            let created_stmt = syn::parse_str(&created_field).unwrap();
-                            create_set_fields.push(created_stmt);
-
-                            let val_set = format!(
-                                r#"if self.created == 0 {{
-                                builder = builder.bind(common::utils::dt::now_utc_micro());
-                            }}"#
-                            );
-                            let if_set = syn::parse_str(&val_set).unwrap();
-                            create_builder_fields.push(if_set);
-
-                            continue;
-                        }
+        let mut name_column = None;
 
-                        // 创建记录 - updated 字段
-                        if field_name == "updated" {
-                            // 创建记录 - updated 字段
-                            let created_field = format!(
-                                r#"{{
-                                fields.push("updated".to_owned());
-                                values.push(format!("${{}}", index));
-                                index += 1;
-                            }}"#
-                            );
-                            let created_stmt = syn::parse_str(&created_field).unwrap();
-                            create_set_fields.push(created_stmt);
-                            // 创建记录 - updated builder
-                            let created_builder_field = format!(
-                                r#"{{
-                                builder = builder.bind(common::utils::dt::now_utc_micro());
-                            }}"#
-                            );
-                            let created_builder_stmt =
-                                syn::parse_str(&created_builder_field).unwrap();
-                            create_builder_fields.push(created_builder_stmt);
-                            // 修改记录 - updated 字段
-                            let updated_field = format!(
-                                r#"{{
-                                values.push(format!("updated = ${{}}", index));
-                                index += 1;
-                            }}"#
-                            );
-                            let updated_stmt = syn::parse_str(&updated_field).unwrap();
-                            updated_set_fields.push(updated_stmt);
-                            // 修改记录 - updated builder
-                            let updated_builder_field = format!(
-                                r#"{{
-                                builder = builder.bind(common::utils::dt::now_utc_micro());
-                            }}"#
-                            );
-                            let updated_builder_stmt =
-                                syn::parse_str(&updated_builder_field).unwrap();
-                            updated_builder_fields.push(updated_builder_stmt);
-
-                            continue;
-                        }
+        for field in fields.iter().filter(|f| !f.skip) {
+            let field_name = field.name.as_str();
+            let field_ident = &field.ident;
+            let field_type = field.ty.to_token_stream().to_string();
+            let column = field.sql_column();
 
-                        // 创建记录 - 依据条件
-                        if field_type == "String" {
-                            let create_field = format!(
-                                r#"
-                            if self.{} != "" {{
-                                fields.push("{}".to_owned());
-                                values.push(format!("${{}}", index));
-                                index += 1;
-                            }}
-                            "#,
-                                field_name, field_name
-                            );
-                            let create_field_stmt =
-                                syn::parse_str(&create_field).expect("解析判断默认字段的值失败");
-                            create_set_fields.push(create_field_stmt);
-                            // 创建记录 - builder
-                            let create_builder = format!(
-                                r#"if self.{} != "" {{
-                                builder = builder.bind(&self.{});
-                            }}"#,
-                                field_name, field_name
-                            );
-                            let create_builder_stmt = syn::parse_str(&create_builder).unwrap();
-                            create_builder_fields.push(create_builder_stmt);
-
-                            // 修改记录 - 字符串字段
-                            let update_field = format!(
-                                r#"if self.{} != "" {{
-                                values.push(format!("{} = ${{}}", index));
-                                index += 1;
-                            }}"#,
-                                field_name, field_name
-                            );
-                            let update_field_stmt = syn::parse_str(&update_field).unwrap();
-                            update_set_fields.push(update_field_stmt);
-                            // 修改记录 - 字符串字段 builder
-                            let update_builder = format!(
-                                r#"if self.{} != "" {{
-                                builder = builder.bind(&self.{});
-                            }}"#,
-                                field_name, field_name
-                            );
-                            let update_builder_stmt = syn::parse_str(&update_builder).unwrap();
-                            update_builder_fields.push(update_builder_stmt);
-                        } else if matches!(field_type.as_str(), "i8" | "i16" | "i32" | "i64") {
-                            let create_field = format!(
-                                r#"{{
-                                fields.push("{}".to_owned());
-                                values.push(format!("${{}}", index));
-                                index += 1;
-                            }}"#,
-                                field_name
-                            );
-                            let create_field_stmt = syn::parse_str(&create_field)
-                                .expect("解析判断默认字段的值数字失败");
-                            create_set_fields.push(create_field_stmt);
-                            // 创建记录 - builder
-                            let create_builder = format!(
-                                r#"
-                                builder = builder.bind(&self.{});
-                            "#,
-                                field_name
-                            );
-                            let create_builder_stmt = syn::parse_str(&create_builder).unwrap();
-                            create_builder_fields.push(create_builder_stmt);
-
-                            // 修改记录 - 一般字段
-                            let update_field = format!(
-                                r#"{{
-                                values.push(format!("{} = ${{}}", index));
-                                index += 1;
-                            }}"#,
-                                field_name
-                            );
-                            let update_field_stmt = syn::parse_str(&update_field).unwrap();
-                            update_set_fields.push(update_field_stmt);
-                            // 修改记录 - 一般字段 builder
-                            let update_builder = format!(
-                                r#"
-                                builder = builder.bind(&self.{});
-                            "#,
-                                field_name
-                            );
-                            let update_builder_stmt = syn::parse_str(&update_builder).unwrap();
-                            update_builder_fields.push(update_builder_stmt);
-                        }
+            table_fields.push(field.select_column());
+            if field_name == "id" {
+                has_id = true;
+            }
+            if field.is_auto_id(&pk_fields) {
+                continue;
+            }
+            if field_name == "name" {
+                name_column = Some(column.to_owned());
+            }
+            if !field.writable() {
+                continue;
+            }
+            // 微秒时间戳 (i64) 的 created / updated 由这里自动填充, 其他类型按普通字段处理
+            let is_timestamp = field_type == "i64";
+            let auto_created = field_name == "created" && is_timestamp;
+            // 主键只用于定位记录, 不在 upsert / save 中更新
+            let is_pk = pk_fields.iter().any(|pk| pk.ident == field.ident);
+            if !auto_created && !field.version && !is_pk {
+                upsert_columns.push(column.to_owned());
+            }
+            // 创建记录 - created 字段
+            if field_name == "created" && is_timestamp {
+                create_set_fields.push(quote! {
+                    {
+                        fields.push(#column.to_owned());
+                        values.push(#dialect.placeholder(index));
+                        index += 1;
+                    }
+                });
+                // 为 0 时使用当前时间, 否则写入指定的值
+                create_builder_fields.push(quote! {
+                    if self.#field_ident == 0 {
+                        builder = builder.bind(#krate::now_utc_micro());
+                    } else {
+                        builder = builder.bind(self.#field_ident);
+                    }
+                });
+                continue;
+            }
+
+            // 创建记录 - updated 字段
+            if field_name == "updated" && is_timestamp {
+                create_set_fields.push(quote! {
+                    {
+                        fields.push(#column.to_owned());
+                        values.push(#dialect.placeholder(index));
+                        index += 1;
+                    }
+                });
+                // 创建记录 - updated builder
+                create_builder_fields.push(quote! {
+                    {
+                        builder = builder.bind(#krate::now_utc_micro());
+                    }
+                });
+                // 修改记录 - updated 字段
+                let updated_value = format!("{} = ", column);
+                updated_set_fields.push(quote! {
+                    {
+                        values.push(format!("{}{}", #updated_value, #dialect.placeholder(index)));
+                        index += 1;
+                    }
+                });
+                // 修改记录 - updated builder
+                updated_builder_fields.push(quote! {
+                    {
+                        builder = builder.bind(#krate::now_utc_micro());
                     }
+                });
+                continue;
+            }
+
+            let update_value = format!("{} = ", column);
+            // 其他类型都直接绑定; 类型未实现 `Encode + Type` 时, 编译错误指向该字段的类型
+            let self_value = field.bind_value();
+            let bind_field = quote_spanned! {field.ty.span()=>
+                builder = builder.bind(#self_value);
+            };
+            if field.is_option() {
+                // 创建记录 - None 时不写入, 使用数据库的默认值
+                create_set_fields.push(quote! {
+                    if self.#field_ident.is_some() {
+                        fields.push(#column.to_owned());
+                        values.push(#dialect.placeholder(index));
+                        index += 1;
+                    }
+                });
+                create_builder_fields.push(quote! {
+                    if self.#field_ident.is_some() {
+                        #bind_field
+                    }
+                });
+            } else {
+                create_set_fields.push(quote! {
+                    {
+                        fields.push(#column.to_owned());
+                        values.push(#dialect.placeholder(index));
+                        index += 1;
+                    }
+                });
+                // 创建记录 - builder
+                create_builder_fields.push(bind_field.clone());
+            }
+            // 修改记录 - 全部写入, None 写入 NULL; 主键只用于定位记录
+            if is_pk {
+                continue;
+            }
+            // 修改记录 - 版本号加 1, 与 updated 一样由生成的代码维护
+            if field.version {
+                let version_value = format!("{0} = {0} + 1", column);
+                updated_set_fields.push(quote! {
+                    values.push(#version_value.to_owned());
+                });
+                if backend == Backend::MySql {
+                    upsert_version.push(quote!(#version_value.to_owned()));
+                } else {
+                    upsert_version.push(quote!(
+                        format!("{0} = {1}.{0} + 1", #column, Self::get_table_name())
+                    ));
+                }
+                continue;
+            }
+            update_set_fields.push(quote! {
+                {
+                    values.push(format!("{}{}", #update_value, #dialect.placeholder(index)));
+                    index += 1;
                 }
-                _ => panic!("Expected named fields"),
-            },
-            _ => panic!("Expected a struct"),
+            });
+            // 修改记录 - builder
+            update_builder_fields.push(bind_field);
         }
         let all_fields = table_fields.join(",");
         tokens.push(quote! {
@@ -514,57 +835,172 @@ pub fn impl_crud_table(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 #all_fields
             }
         });
-        if has_id && has_name {
+        if let (true, Some(name_column)) = (has_id, name_column) {
+            // id 和 name 列
+            let id_name_fields = format!(
+                "{},{}",
+                if id_column == backend.quote_ident("id") {
+                    id_column.to_owned()
+                } else {
+                    format!("{} AS id", id_column)
+                },
+                if name_column == backend.quote_ident("name") {
+                    name_column
+                } else {
+                    format!("{} AS name", name_column)
+                }
+            );
             tokens.push(quote! {
                 /// get_all_id_names: 获取全部记录的 id 和 name
-                pub async fn get_all_id_names(pool: &common::types::Db) -> Result<Vec<common::types::IdName>, &'static str> {
-                    let sql = format!("SELECT id,name FROM {}", Self::get_table_name());
-                    match sqlx::query_as::<_, common::types::IdName>(&sql).fetch_all(pool).await {
+                pub async fn get_all_id_names<'e, E>(executor: E) -> Result<Vec<#krate::IdName>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let sql = format!("SELECT {} FROM {}{}", #id_name_fields, Self::get_table_name(), #soft_where);
+                    sqlx::query_as::<_, #krate::IdName>(&sql).fetch_all(executor).await.map_err(#krate::CrudError::from)
+                }
+
+                /// get_all_id_names_by_cond: 获取带分页的全部记录的 id 和 name
+                pub async fn get_all_id_names_by_cond<'e, E>(executor: E, cond: &#krate::Cond) -> Result<Vec<#krate::IdName>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    cond.check()?;
+                    let sql_cond = #build_cond;
+                    let where_str = if !sql_cond.is_empty() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
+                    let order_sort = if let Some(v) = Self::order_by_sql(cond)? { format!("ORDER BY {}", v) } else { "".to_string() };
+                    let sql = format!("SELECT {} FROM {} {} {}", #id_name_fields, Self::get_table_name(), where_str, order_sort);
+                    let mut builder = sqlx::query_as::<_, #krate::IdName>(&sql);
+                    builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                    match builder.fetch_all(executor).await {
                         Ok(v) => Ok(v),
                         Err(err) => {
-                            println!("get_all_id_names error: {:?}", err);
-                            Err("获取数据失败")
+                            #krate::log::error!("依据条侦探获取数据失败: {:?},\nSQL: {}", err, sql);
+                            Err(err.into())
                         }
                     }
                 }
+            });
+        }
+        // 批量写入 (create_many / copy_in) 的列: 固定的列集合, None 写入 NULL
+        let mut insert_columns: Vec<String> = vec![];
+        let mut many_binds: Vec<proc_macro2::TokenStream> = vec![];
+        let mut copy_writes: Vec<proc_macro2::TokenStream> = vec![];
+        let mut copy_bounds: Vec<proc_macro2::TokenStream> = vec![];
+        for field in fields
+            .iter()
+            .filter(|f| f.writable() && !f.is_auto_id(&pk_fields))
+        {
+            let field_ident = &field.ident;
+            let is_timestamp = field.ty.to_token_stream().to_string() == "i64";
+            insert_columns.push(field.sql_column());
+            let value = if field.name == "created" && is_timestamp {
+                quote!(if row.#field_ident == 0 { #krate::now_utc_micro() } else { row.#field_ident })
+            } else if field.name == "updated" && is_timestamp {
+                quote!(#krate::now_utc_micro())
+            } else {
+                // 写成 for<'r> 的约束, 字段类型没有实现 CopyValue 时只在调用 copy_in 时报错
+                let ty = &field.ty;
+                copy_bounds.push(quote!(for<'r> &'r #ty: #krate::CopyValue));
+                quote_spanned!(field.ty.span()=> &row.#field_ident)
+            };
+            many_binds.push(quote!(builder = builder.bind(#value);));
+            copy_writes.push(quote_spanned! {field.ty.span()=>
+                #krate::CopyValue::write_copy(&#value, &mut buf);
+            });
+        }
+        let insert_fields = insert_columns.join(",");
+        let insert_count = insert_columns.len().max(1);
+        // 单条语句的绑定参数上限: PostgreSQL / MySQL 65535, SQLite 32766
+        let max_binds: usize = if backend == Backend::Sqlite {
+            32766
+        } else {
+            65535
+        };
+        tokens.push(quote! {
+            /// create_many / copy_in 写入的列
+            #[inline]
+            pub fn get_insert_fields() -> &'static str {
+                #insert_fields
+            }
 
-                /// get_all_id_names_by_cond: 获取带分页的全部记录的 id 和 name
-                pub async fn get_all_id_names_by_cond(pool: &common::types::Db, cond: &common::types::Cond) -> Result<Vec<common::types::IdName>, &'static str> {
-                    let sql_cond = cond.build();
-                    let where_str = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { sql_cond.to_owned() };
-                    let order_sort = if let Some(v) = cond.get_order_by() { format!("ORDER BY {}", v) } else { "".to_string() };
-                    let sql = format!("SELECT id,name FROM {} {} {}", Self::get_table_name(), where_str, order_sort);
-                    let mut builder = sqlx::query_as::<_, common::types::IdName>(&sql);
-                    for val in &cond.args {
-                        match val {
-                            common::types::Val::I8(rv) =>   { builder = builder.bind::<i8>(*rv);        },
-                            common::types::Val::U8(rv) =>   { builder = builder.bind::<i8>(*rv as i8);  },
-                            common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv);       },
-                            common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16);},
-                            common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv);       },
-                            common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32);},
-                            common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv);       },
-                            common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64);},
-                            common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv);       },
-                            common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv);       },
-                            common::types::Val::Str(rv) =>  { builder = builder.bind(rv);               },
-                            common::types::Val::S(rv) =>    { builder = builder.bind(rv);               },
-                            common::types::Val::Bool(rv) => { builder = builder.bind(rv);               },
-                            _ => { continue; }
-                        };
+            /// 批量创建记录: 多行 INSERT, 按绑定参数上限分批执行; 返回插入的记录数
+            ///
+            /// 与 `create` 不同, None 的字段写入 NULL; 需要全部成功或全部失败时传入事务
+            pub fn create_many<'a, A>(db: A, rows: &'a [Self]) -> impl std::future::Future<Output = Result<u64, #krate::CrudError>> + Send + 'a
+            where
+                A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+            {
+                async move {
+                    if rows.is_empty() {
+                        return Ok(0);
                     }
-                    match builder.fetch_all(pool).await {
-                        Ok(v) => Ok(v),
-                        Err(err) => {
-                            log::error!("依据条侦探获取数据失败: {:?},\nSQL: {}", err, sql);
-                            Err("获取数据失败: get_all_by_cond - select")
+                    for row in rows {
+                        row.validate()?;
+                    }
+                    let mut conn = db.acquire().await?;
+                    let mut total = 0;
+                    for chunk in rows.chunks(#max_binds / #insert_count) {
+                        let mut index = 1;
+                        let mut values: Vec<String> = Vec::with_capacity(chunk.len());
+                        for _ in chunk {
+                            let mut row_values: Vec<String> = Vec::with_capacity(#insert_count);
+                            for _ in 0..#insert_count {
+                                row_values.push(#dialect.placeholder(index));
+                                index += 1;
+                            }
+                            values.push(format!("({})", row_values.join(",")));
+                        }
+                        let sql = format!("INSERT INTO {} ({}) VALUES {}", Self::get_table_name(), Self::get_insert_fields(), values.join(","));
+                        let mut builder = sqlx::query(&sql);
+                        for row in chunk {
+                            #(#many_binds)*
                         }
+                        total += builder.execute(&mut *conn).await?.rows_affected();
+                    }
+                    Ok(total)
+                }
+            }
+        });
+        if backend == Backend::Postgres && !insert_columns.is_empty() {
+            tokens.push(quote! {
+                /// 使用 `COPY ... FROM STDIN` 批量写入, 适合大量数据; 返回写入的记录数
+                pub fn copy_in<'a, A, S>(db: A, rows: S) -> impl std::future::Future<Output = Result<u64, #krate::CrudError>> + Send + 'a
+                where
+                    A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+                    S: #krate::futures_core::Stream<Item = Self> + Send + 'a,
+                    #(#copy_bounds,)*
+                {
+                    async move {
+                        let mut conn = db.acquire().await?;
+                        let sql = format!("COPY {} ({}) FROM STDIN", Self::get_table_name(), Self::get_insert_fields());
+                        let mut copy = conn.copy_in_raw(&sql).await?;
+                        let mut rows = std::pin::pin!(rows);
+                        let mut buf = String::new();
+                        while let Some(row) = std::future::poll_fn(|cx| rows.as_mut().poll_next(cx)).await {
+                            row.validate()?;
+                            #(
+                                #copy_writes
+                                buf.push('\t');
+                            )*
+                            buf.pop();
+                            buf.push('\n');
+                            if buf.len() >= 64 * 1024 {
+                                copy.send(buf.as_bytes()).await?;
+                                buf.clear();
+                            }
+                        }
+                        if !buf.is_empty() {
+                            copy.send(buf.as_bytes()).await?;
+                        }
+                        Ok(copy.finish().await?)
                     }
                 }
             });
         }
         tokens.push(quote! {
-            pub async fn create(&self, pool: &common::types::Db) -> Result<(), &'static str> {
+            /// INSERT 语句 (不含 ON CONFLICT / RETURNING), 占位符与 create 的 builder 顺序一致
+            fn create_sql(&self) -> String {
                 let mut insert_sql = String::from("INSERT INTO ");
                 insert_sql.push_str(Self::get_table_name());
                 insert_sql.push_str(" (");
@@ -576,232 +1012,857 @@ pub fn impl_crud_table(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 insert_sql.push_str(") VALUES (");
                 insert_sql.push_str(&values.join(","));
                 insert_sql.push_str(")");
+                insert_sql
+            }
+
+            /// 创建记录
+            #create_vis async fn #create_fn<'e, E>(&self, executor: E) -> Result<(), #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                self.validate()?;
+                let insert_sql = self.create_sql();
                 let mut builder = sqlx::query(&insert_sql);
                 #(#create_builder_fields)*
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("create error: {}", e);
-                        Err("记录创建失败")
-                    }
-                }
+                builder.execute(executor).await?;
+                Ok(())
             }
+
         });
-        tokens.push(quote!{
-            pub async fn create_or_skip_by(&self, pool: &common::types::Db, skip_field: &'static str) -> Result<(), &'static str> {
-                let mut insert_sql = String::from("INSERT INTO ");
-                insert_sql.push_str(Self::get_table_name());
-                insert_sql.push_str(" (");
-                let mut fields: Vec<String> = vec![];
-                let mut values: Vec<String> = vec![];
-                let mut index = 1;
-                #(#create_set_fields)*
-                insert_sql.push_str(&fields.join(","));
-                insert_sql.push_str(") VALUES (");
-                insert_sql.push_str(&values.join(","));
-                insert_sql.push_str(") ON CONFLICT (");
-                insert_sql.push_str(skip_field);
-                insert_sql.push_str(") DO NOTHING");
-                let mut builder = sqlx::query(&insert_sql);
-                #(#create_builder_fields)*
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("create or skip error: {}", e);
-                        Err("记录创建失败")
-                    }
+        if backend == Backend::MySql {
+            // MySQL 不支持 RETURNING / ON CONFLICT; INSERT IGNORE 会把 NOT NULL / 外键等错误也当作警告跳过,
+            // 因此只在 skip_field 的值不存在时插入
+            let skip_arms = fields.iter().filter(|f| !f.skip).map(|f| {
+                let variant = Ident::new(&pascalcase::to_pascal_case(&f.name), f.ident.span());
+                let value = f.bind_value();
+                quote!(#column_ident::#variant => builder.bind(#value),)
+            });
+            tokens.push(quote! {
+                /// 创建记录, skip_field 的值已存在时跳过; 返回是否插入了记录
+                ///
+                /// MySQL 没有 `ON CONFLICT (列)`, 使用 `INSERT ... SELECT ... WHERE NOT EXISTS`: 其他错误 (包括其他唯一键冲突) 照常返回;
+                /// 并发插入同一个值时, 后插入的一方返回 `CrudError::UniqueViolation` 而不是 `false`
+                #skip_vis async fn #skip_fn<'e, E>(&self, executor: E, skip_field: #column_ident) -> Result<bool, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    self.validate()?;
+                    let mut fields: Vec<String> = vec![];
+                    let mut values: Vec<String> = vec![];
+                    let mut index = 1;
+                    #(#create_set_fields)*
+                    let insert_sql = format!(
+                        "INSERT INTO {0} ({1}) SELECT {2} FROM DUAL WHERE NOT EXISTS (SELECT 1 FROM {0} WHERE {3} = ?)",
+                        Self::get_table_name(),
+                        fields.join(","),
+                        values.join(","),
+                        skip_field.as_str()
+                    );
+                    let mut builder = sqlx::query(&insert_sql);
+                    #(#create_builder_fields)*
+                    builder = match skip_field {
+                        #(#skip_arms)*
+                    };
+                    let result = builder.execute(executor).await?;
+                    Ok(result.rows_affected() > 0)
                 }
+            });
+            if let Some(id_field) = id_field {
+                let id_type = &id_field.ty;
+                let id_ident = &id_field.ident;
+                let id_value = id_field.bind_value();
+                // 自增 id 通过 LAST_INSERT_ID() 取回记录, 其他 id 由调用方提供, 按写入的值查询
+                let (select_created, returning_id, upsert_id_set, upsert_select) = if id_field
+                    .is_auto_id(&pk_fields)
+                {
+                    let select_last = format!("WHERE {} = LAST_INSERT_ID()", id_column);
+                    (
+                        quote! {
+                            let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), #select_last);
+                            Ok(sqlx::query_as::<_, Self>(&sql).fetch_one(&mut *conn).await?)
+                        },
+                        quote! {
+                            let result = builder.execute(executor).await?;
+                            <#id_type>::try_from(result.last_insert_id())
+                                .map_err(|e| #krate::CrudError::Database(sqlx::Error::Decode(Box::new(e))))
+                        },
+                        // LAST_INSERT_ID(id) 让更新的情况也能取到记录的 id
+                        format!("{0} = LAST_INSERT_ID({0})", id_column),
+                        quote! {
+                            let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), #select_last);
+                            Ok(sqlx::query_as::<_, Self>(&sql).fetch_one(&mut *conn).await?)
+                        },
+                    )
+                } else {
+                    let select_id = format!("WHERE {} = ?", id_column);
+                    let id_variant =
+                        Ident::new(&pascalcase::to_pascal_case(&id_field.name), id_ident.span());
+                    // 按冲突列查询结果记录 (更新时 id 可能与写入的不同)
+                    let key_arms = fields.iter().filter(|f| f.writable()).map(|f| {
+                        let variant =
+                            Ident::new(&pascalcase::to_pascal_case(&f.name), f.ident.span());
+                        let value = f.bind_value();
+                        quote!(#column_ident::#variant => builder.bind(#value),)
+                    });
+                    (
+                        quote! {
+                            let sql = format!("SELECT {} FROM {} {}", Self::get_fields(), Self::get_table_name(), #select_id);
+                            Ok(sqlx::query_as::<_, Self>(&sql).bind(#id_value).fetch_one(&mut *conn).await?)
+                        },
+                        quote! {
+                            builder.execute(executor).await?;
+                            Ok(self.#id_ident.clone())
+                        },
+                        // 没有需要更新的列时也是合法的 ON DUPLICATE KEY UPDATE
+                        format!("{0} = {0}", id_column),
+                        quote! {
+                            let keys: Vec<#column_ident> = if conflict.is_empty() { vec![#column_ident::#id_variant] } else { conflict.to_vec() };
+                            let wheres: Vec<String> = keys.iter().map(|c| format!("{} = ?", c.as_str())).collect();
+                            let sql = format!("SELECT {} FROM {} WHERE {}", Self::get_fields(), Self::get_table_name(), wheres.join(" AND "));
+                            let mut builder = sqlx::query_as::<_, Self>(&sql);
+                            for key in keys {
+                                #[allow(unreachable_patterns)]
+                                {
+                                    builder = match key {
+                                        #(#key_arms)*
+                                        _ => builder,
+                                    };
+                                }
+                            }
+                            Ok(builder.fetch_one(&mut *conn).await?)
+                        },
+                    )
+                };
+                tokens.push(quote! {
+                    /// 创建记录并返回插入后的记录 - 需要在同一连接上查询 LAST_INSERT_ID(), 因此接收 `Acquire`
+                    #returning_vis fn #returning_fn<'a, A>(&'a self, db: A) -> impl std::future::Future<Output = Result<Self, #krate::CrudError>> + Send + 'a
+                    where
+                        A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+                    {
+                        async move {
+                            self.validate()?;
+                            let mut conn = db.acquire().await?;
+                            let insert_sql = self.create_sql();
+                            let mut builder = sqlx::query(&insert_sql);
+                            #(#create_builder_fields)*
+                            builder.execute(&mut *conn).await?;
+                            #select_created
+                        }
+                    }
+
+                    /// 创建记录并返回 id (自增 id 为 LAST_INSERT_ID())
+                    #returning_id_vis async fn #returning_id_fn<'e, E>(&self, executor: E) -> Result<#id_type, #krate::CrudError>
+                    where
+                        E: sqlx::Executor<'e, Database = #db>,
+                    {
+                        self.validate()?;
+                        let insert_sql = self.create_sql();
+                        let mut builder = sqlx::query(&insert_sql);
+                        #(#create_builder_fields)*
+                        #returning_id
+                    }
+
+                    /// 创建或更新记录 (`ON DUPLICATE KEY UPDATE`), 返回结果记录
+                    ///
+                    /// MySQL 依据表上任意的唯一索引判断冲突, `conflict` 只用于从 `UpsertUpdate::All` 中排除这些列
+                    pub fn upsert<'a, A>(&'a self, db: A, conflict: &'a [#column_ident], update: #krate::UpsertUpdate<#column_ident>) -> impl std::future::Future<Output = Result<Self, #krate::CrudError>> + Send + 'a
+                    where
+                        A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+                    {
+                        async move {
+                            self.validate()?;
+                            let all_columns: &[&str] = &[#(#upsert_columns),*];
+                            let conflict_columns: Vec<&str> = conflict.iter().map(|c| c.as_str()).collect();
+                            let mut sets = vec![#upsert_id_set.to_owned()];
+                            match &update {
+                                #krate::UpsertUpdate::All => sets.extend(
+                                    all_columns
+                                        .iter()
+                                        .filter(|c| !conflict_columns.contains(c))
+                                        .map(|c| format!("{0} = VALUES({0})", c)),
+                                ),
+                                #krate::UpsertUpdate::Columns(cols) => sets.extend(cols.iter().map(|c| format!("{0} = VALUES({0})", c.as_str()))),
+                                #krate::UpsertUpdate::Exprs(exprs) => sets.extend(exprs.iter().map(|(c, e)| format!("{} = {}", c.as_str(), e))),
+                            }
+                            #(sets.push(#upsert_version);)*
+                            let mut conn = db.acquire().await?;
+                            let insert_sql = format!("{} ON DUPLICATE KEY UPDATE {}", self.create_sql(), sets.join(","));
+                            let mut builder = sqlx::query(&insert_sql);
+                            #(#create_builder_fields)*
+                            builder.execute(&mut *conn).await?;
+                            #upsert_select
+                        }
+                    }
+                });
             }
-        });
-        tokens.push(quote!{
-            /// 更新记录 - 修改指定字段
-            pub async fn update(&self, pool: &common::types::Db, cond_fields: &[(&'static str, common::types::Val)]) -> Result<(), &'static str> {
-                let mut update_sql = String::from("UPDATE ");
-                update_sql.push_str(Self::get_table_name());
-                update_sql.push_str(" SET ");
-                let mut index = 1;
-                let mut values: Vec<String> = vec![];
-                for (field, _) in cond_fields {
-                    values.push(format!("{} = ${}", field, index));
-                    index += 1;
+        } else {
+            tokens.push(quote! {
+                /// 创建记录并返回插入后的记录 (包括数据库生成的 id / 默认值)
+                #returning_vis async fn #returning_fn<'e, E>(&self, executor: E) -> Result<Self, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    self.validate()?;
+                    let insert_sql = format!("{} RETURNING {}", self.create_sql(), Self::get_fields());
+                    let mut builder = sqlx::query_as::<_, Self>(&insert_sql);
+                    #(#create_builder_fields)*
+                    Ok(builder.fetch_one(executor).await?)
                 }
-                #(#updated_set_fields)*
-                update_sql.push_str(&values.join(","));
-                update_sql.push_str(&format!(" WHERE id = {}", self.id));
-                let mut builder = sqlx::query(&update_sql);
-                for (_, val) in cond_fields {
-                    match val {
-                        common::types::Val::I8(rv)  => { builder = builder.bind::<i8>(*rv); },
-                        common::types::Val::U8(rv)  => { builder = builder.bind::<i8>(*rv as i8); },
-                        common::types::Val::I16(rv) => { builder = builder.bind::<i16>(*rv); },
-                        common::types::Val::U16(rv) => { builder = builder.bind::<i16>(*rv as i16); },
-                        common::types::Val::I32(rv) => { builder = builder.bind::<i32>(*rv); },
-                        common::types::Val::U32(rv) => { builder = builder.bind::<i32>(*rv as i32); },
-                        common::types::Val::I64(rv) => { builder = builder.bind::<i64>(*rv); },
-                        common::types::Val::U64(rv) => { builder = builder.bind::<i64>(*rv as i64); },
-                        common::types::Val::F32(rv) => { builder = builder.bind::<f32>(*rv); },
-                        common::types::Val::F64(rv) => { builder = builder.bind::<f64>(*rv); },
-                        common::types::Val::Str(rv) => { builder = builder.bind(rv); },
-                        common::types::Val::S(rv)   => { builder = builder.bind(rv); },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv); },
-                        _ => { continue; }
-                    }
-                }
-                #(#updated_builder_fields)*
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("依据ID修改单个记录出错: {}", e);
-                        Err("记录修改失败: 依据ID")
+
+                /// 创建或更新记录 (`ON CONFLICT (...) DO UPDATE`), 返回结果记录;
+                /// `conflict` 为空时使用主键 (不包括数据库生成的 id), 没有可用的主键时返回 `CrudError::Validation`
+                pub async fn upsert<'e, E>(&self, executor: E, conflict: &[#column_ident], update: #krate::UpsertUpdate<#column_ident>) -> Result<Self, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    self.validate()?;
+                    let conflict_columns: Vec<&str> = if conflict.is_empty() {
+                        vec![#(#upsert_pk_columns),*]
+                    } else {
+                        conflict.iter().map(|c| c.as_str()).collect()
+                    };
+                    if conflict_columns.is_empty() {
+                        return Err(#krate::CrudError::Validation(format!("{} 没有可用作冲突目标的主键, 需要指定 conflict", Self::get_table_name())));
+                    }
+                    let all_columns: &[&str] = &[#(#upsert_columns),*];
+                    let mut sets: Vec<String> = match &update {
+                        #krate::UpsertUpdate::All => all_columns
+                            .iter()
+                            .filter(|c| !conflict_columns.contains(c))
+                            .map(|c| format!("{0} = EXCLUDED.{0}", c))
+                            .collect(),
+                        #krate::UpsertUpdate::Columns(cols) => cols.iter().map(|c| format!("{0} = EXCLUDED.{0}", c.as_str())).collect(),
+                        #krate::UpsertUpdate::Exprs(exprs) => exprs.iter().map(|(c, e)| format!("{} = {}", c.as_str(), e)).collect(),
+                    };
+                    #(sets.push(#upsert_version);)*
+                    // 没有需要更新的列时更新冲突列本身, 以便 RETURNING 返回已有的记录
+                    if sets.is_empty() {
+                        sets = conflict_columns.iter().map(|c| format!("{0} = EXCLUDED.{0}", c)).collect();
                     }
+                    let insert_sql = format!(
+                        "{} ON CONFLICT ({}) DO UPDATE SET {} RETURNING {}",
+                        self.create_sql(),
+                        conflict_columns.join(","),
+                        sets.join(","),
+                        Self::get_fields()
+                    );
+                    let mut builder = sqlx::query_as::<_, Self>(&insert_sql);
+                    #(#create_builder_fields)*
+                    Ok(builder.fetch_one(executor).await?)
+                }
+
+                /// 创建记录, 冲突时跳过; 返回是否插入了记录
+                #skip_vis async fn #skip_fn<'e, E>(&self, executor: E, skip_field: #column_ident) -> Result<bool, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    self.validate()?;
+                    let mut insert_sql = self.create_sql();
+                    insert_sql.push_str(" ON CONFLICT (");
+                    insert_sql.push_str(skip_field.as_str());
+                    insert_sql.push_str(") DO NOTHING");
+                    let mut builder = sqlx::query(&insert_sql);
+                    #(#create_builder_fields)*
+                    let result = builder.execute(executor).await?;
+                    Ok(result.rows_affected() > 0)
                 }
+            });
+            if let Some(id_field) = id_field {
+                let id_type = &id_field.ty;
+                let returning_id = format!(" RETURNING {}", id_column);
+                tokens.push(quote! {
+                    /// 创建记录并返回 id
+                    #returning_id_vis async fn #returning_id_fn<'e, E>(&self, executor: E) -> Result<#id_type, #krate::CrudError>
+                    where
+                        E: sqlx::Executor<'e, Database = #db>,
+                    {
+                        self.validate()?;
+                        let insert_sql = format!("{}{}", self.create_sql(), #returning_id);
+                        let mut builder = sqlx::query_scalar::<_, #id_type>(&insert_sql);
+                        #(#create_builder_fields)*
+                        Ok(builder.fetch_one(executor).await?)
+                    }
+                });
             }
+        }
+        // update_by_cond 的绑定顺序: MySQL 的 `?` 按 SQL 中出现的顺序 (SET 在 WHERE 前), 其他按占位符编号 (条件在前)
+        let bind_cond_args = quote! {
+            builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+        };
+        let bind_cond_fields = quote! {
+            builder = #krate::BindVal::bind_vals(builder, cond_fields.iter().map(|(_, val)| val))?;
+            #(#updated_builder_fields)*
+        };
+        let update_by_cond_binds = if backend == Backend::MySql {
+            quote!(#bind_cond_fields #bind_cond_args)
+        } else {
+            quote!(#bind_cond_args #bind_cond_fields)
+        };
+        if !pk_fields.is_empty() {
+            tokens.push(quote!{
+                /// 更新记录 - 修改指定字段
+                #update_vis async fn #update_fn<'e, E>(&self, executor: E, cond_fields: &[(#column_ident, #krate::Val)]) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut update_sql = String::from("UPDATE ");
+                    update_sql.push_str(Self::get_table_name());
+                    update_sql.push_str(" SET ");
+                    let mut index = 1;
+                    let mut values: Vec<String> = vec![];
+                    for (field, _) in cond_fields {
+                        values.push(format!("{} = {}", field.as_str(), #dialect.placeholder(index)));
+                        index += 1;
+                    }
+                    #(#updated_set_fields)*
+                    update_sql.push_str(&values.join(","));
+                    update_sql.push_str(" WHERE ");
+                    update_sql.push_str(&#pk_where);
+                    update_sql.push_str(#version_where);
+                    let mut builder = sqlx::query(&update_sql);
+                    builder = #krate::BindVal::bind_vals(builder, cond_fields.iter().map(|(_, val)| val))?;
+                    #(#updated_builder_fields)*
+                    #(#pk_self_binds)*
+                    #version_bind
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#no_rows_error);
+                    }
+                    Ok(())
+                }
+            });
+        }
+        tokens.push(quote!{
             /// 更新记录 - 依据多个条件修改字段
-            pub async fn update_by_cond(pool: &common::types::Db, cond_fields: &[(&'static str, common::types::Val)], cond: &common::types::Cond) -> Result<(), &'static str> {
+            pub async fn update_by_cond<'e, E>(executor: E, cond_fields: &[(#column_ident, #krate::Val)], cond: &#krate::Cond) -> Result<(), #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
                 let mut update_sql = String::from("UPDATE ");
                 update_sql.push_str(Self::get_table_name());
                 update_sql.push_str(" SET ");
-                let mut index = cond.arg_count + 1;
+                let mut index = cond.arg_count() + 1;
                 let mut values: Vec<String> = vec![];
                 for (field, _) in cond_fields {
-                    values.push(format!("{} = ${}", field, index));
+                    values.push(format!("{} = {}", field.as_str(), #dialect.placeholder(index)));
                     index += 1;
                 }
                 #(#updated_set_fields)*
                 update_sql.push_str(&values.join(","));
-                let sql_cond = cond.build();
-                let where_str = if cond.has_args() { format!("WHERE {}", &sql_cond) } else { sql_cond };
+                cond.check()?;
+                let sql_cond = cond.build_for(#dialect);
+                let where_str = if cond.has_args() { format!(" WHERE {}", &sql_cond) } else { sql_cond };
                 update_sql.push_str(&where_str);
                 let mut builder = sqlx::query(&update_sql);
-                for val in &cond.args {
-                    match val {
-                        common::types::Val::I8(rv) =>   { builder = builder.bind::<i8>(*rv);        },
-                        common::types::Val::U8(rv) =>   { builder = builder.bind::<i8>(*rv as i8);  },
-                        common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv);       },
-                        common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16);},
-                        common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv);       },
-                        common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32);},
-                        common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv);       },
-                        common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64);},
-                        common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv);       },
-                        common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv);       },
-                        common::types::Val::Str(rv) =>  { builder = builder.bind(rv);               },
-                        common::types::Val::S(rv) =>    { builder = builder.bind(rv);               },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv);               },
-                        _ => { continue; }
-                    }
-                }
-                for (_, val) in cond_fields {
-                    match val {
-                        common::types::Val::I8(rv)  =>  { builder = builder.bind::<i8>(*rv);        },
-                        common::types::Val::U8(rv)  =>  { builder = builder.bind::<i8>(*rv as i8);  },
-                        common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv);       },
-                        common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16);},
-                        common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv);       },
-                        common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32);},
-                        common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv);       },
-                        common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64);},
-                        common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv);       },
-                        common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv);       },
-                        common::types::Val::Str(rv) =>  { builder = builder.bind(rv);               },
-                        common::types::Val::S(rv)   =>  { builder = builder.bind(rv);               },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv);               },
-                        _ => { continue; }
-                    }
-                }
-                #(#updated_builder_fields)*
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("依据条件修改记录出错: {}", e);
-                        Err("记录修改失败: 依据条件")
-                    }
-                }
+                #update_by_cond_binds
+                builder.execute(executor).await?;
+                Ok(())
             }
         });
-        tokens.push(quote! {
-            /// 更新记录 - 修改所有字段
-            pub async fn save(&self, pool: &common::types::Db) -> Result<(), &'static str> {
-                let mut save_sql = String::from("UPDATE ");
-                save_sql.push_str(Self::get_table_name());
-                save_sql.push_str(" SET ");
-                let mut index = 1;
-                let mut values: Vec<String> = vec![];
-                #(#update_set_fields)*
-                #(#updated_set_fields)*
-                save_sql.push_str(&values.join(","));
-                save_sql.push_str(&format!(" WHERE id = {}", self.id));
-                let mut builder = sqlx::query(&save_sql);
-                #(#update_builder_fields)*
-                #(#updated_builder_fields)*
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("Update error: {}", e);
-                        Err("记录修改失败")
+        if !pk_fields.is_empty() {
+            tokens.push(quote! {
+                /// 更新记录 - 修改所有字段
+                #save_vis async fn #save_fn<'e, E>(&self, executor: E) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    self.validate()?;
+                    let mut save_sql = String::from("UPDATE ");
+                    save_sql.push_str(Self::get_table_name());
+                    save_sql.push_str(" SET ");
+                    let mut index = 1;
+                    let mut values: Vec<String> = vec![];
+                    #(#update_set_fields)*
+                    #(#updated_set_fields)*
+                    save_sql.push_str(&values.join(","));
+                    save_sql.push_str(" WHERE ");
+                    save_sql.push_str(&#pk_where);
+                    save_sql.push_str(#version_where);
+                    let mut builder = sqlx::query(&save_sql);
+                    #(#update_builder_fields)*
+                    #(#updated_builder_fields)*
+                    #(#pk_self_binds)*
+                    #version_bind
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#no_rows_error);
                     }
+                    Ok(())
                 }
-            }
-        });
-        tokens.push(quote! {
-            /// 删除记录
-            pub async fn delete(&self, pool: &common::types::Db) -> Result<(), &'static str> {
-                let mut delete_sql = String::from("DELETE FROM ");
-                delete_sql.push_str(Self::get_table_name());
-                delete_sql.push_str(&format!(" WHERE id = {}", self.id));
-                match sqlx::query(&delete_sql).execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("依据ID删除记录失败: {}", e);
-                        Err("依据ID记录删除失败")
+
+                /// 删除记录
+                #delete_vis async fn #delete_fn<'e, E>(&self, executor: E) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let delete_sql = format!("{} WHERE {}{}", #delete_head, #pk_where, #soft_and);
+                    let mut builder = sqlx::query(&delete_sql);
+                    #(#pk_self_binds)*
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#krate::CrudError::NotFound);
+                    }
+                    Ok(())
+                }
+
+                /// 依据主键获取记录, 联合主键传入元组
+                pub async fn find_by_pk<'e, E>(executor: E, pk: #pk_type) -> Result<Self, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let sql = format!("SELECT {} FROM {} WHERE {}{}", Self::get_fields(), Self::get_table_name(), #pk_where, #soft_and);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    #(#pk_arg_binds)*
+                    builder.fetch_one(executor).await.map_err(#krate::CrudError::from)
+                }
+
+                /// 依据主键删除记录, 联合主键传入元组
+                pub async fn delete_by_pk<'e, E>(executor: E, pk: #pk_type) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let sql = format!("{} WHERE {}{}", #delete_head, #pk_where, #soft_and);
+                    let mut builder = sqlx::query(&sql);
+                    #(#pk_arg_binds)*
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#krate::CrudError::NotFound);
                     }
+                    Ok(())
                 }
+
+                /// 依据主键判断记录是否存在, 联合主键传入元组
+                pub async fn exists_by_pk<'e, E>(executor: E, pk: #pk_type) -> Result<bool, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let sql = format!("SELECT 1 FROM {} WHERE {}{} LIMIT 1", Self::get_table_name(), #pk_where, #soft_and);
+                    let mut builder = sqlx::query(&sql);
+                    #(#pk_arg_binds)*
+                    Ok(builder.fetch_optional(executor).await?.is_some())
+                }
+            });
+        }
+        if let [pk] = pk_fields.as_slice() {
+            let pk_column = pk.sql_column();
+            let pk_ty = &pk.ty;
+            let (in_where, in_binds, in_bounds) =
+                relation::in_list(backend, &krate, &pk_column, &quote!(#pk_ty));
+            tokens.push(quote! {
+                /// 主键的列名
+                #[inline]
+                pub fn get_pk_column() -> &'static str {
+                    #pk_column
+                }
+
+                /// 依据多个主键获取记录, 一次查询; 返回的顺序不固定
+                pub async fn find_by_pks<'e, E>(executor: E, pks: &[#pk_ty]) -> Result<Vec<Self>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                    #in_bounds
+                {
+                    let ids = pks;
+                    if ids.is_empty() {
+                        return Ok(vec![]);
+                    }
+                    let sql = format!("SELECT {} FROM {} WHERE {}{}", Self::get_fields(), Self::get_table_name(), #in_where, #soft_and);
+                    let mut builder = sqlx::query_as::<_, Self>(&sql);
+                    #in_binds
+                    builder.fetch_all(executor).await.map_err(#krate::CrudError::from)
+                }
+            });
+        }
+        if let [pk] = pk_fields.as_slice() {
+            let pk_ident = &pk.ident;
+            let pk_ty = &pk.ty;
+            let pk_column = pk.sql_column();
+            let pk_bind = quote_spanned!(pk_ty.span()=> builder = builder.bind(pk););
+            tokens.push(quote! {
+                /// for_each_batch: 依据条件按主键顺序分批读取记录, 每批最多 batch_size 条, 依次交给 f 处理; 返回处理的记录数
+                ///
+                /// 使用 `主键 > 上一批最后的主键` 翻页, 适合后台任务遍历大表; 忽略 cond 的排序和分页
+                pub fn for_each_batch<'a, A, F, Fut>(db: A, cond: &'a #krate::Cond, batch_size: usize, mut f: F) -> impl std::future::Future<Output = Result<u64, #krate::CrudError>> + Send + 'a
+                where
+                    A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+                    F: FnMut(Vec<Self>) -> Fut + Send + 'a,
+                    Fut: std::future::Future<Output = Result<(), #krate::CrudError>> + Send,
+                {
+                    async move {
+                        let mut conn = db.acquire().await?;
+                        let batch_size = batch_size.max(1);
+                        cond.check()?;
+                        let sql_cond = #build_cond;
+                        let after = format!("{} > {}", #pk_column, #dialect.placeholder(cond.arg_count() + 1));
+                        let mut last: Option<#pk_ty> = None;
+                        let mut total = 0;
+                        loop {
+                            let wheres: Vec<&str> = [sql_cond.as_str(), if last.is_some() { after.as_str() } else { "" }].into_iter().filter(|w| !w.is_empty()).collect();
+                            let where_str = if wheres.is_empty() { String::new() } else { format!(" WHERE ({})", wheres.join(") AND (")) };
+                            let sql = format!("SELECT {} FROM {}{} ORDER BY {} LIMIT {}", Self::get_fields(), Self::get_table_name(), where_str, #pk_column, batch_size);
+                            let mut builder = sqlx::query_as::<_, Self>(&sql);
+                            builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                            if let Some(pk) = &last {
+                                #pk_bind
+                            }
+                            let rows = builder.fetch_all(&mut *conn).await?;
+                            let count = rows.len();
+                            last = rows.last().map(|row| row.#pk_ident.clone());
+                            total += count as u64;
+                            if count > 0 {
+                                f(rows).await?;
+                            }
+                            if count < batch_size {
+                                return Ok(total);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        // 游标分页: 单一主键, 且主键可以保存到游标中
+        if let Some(pk) = pk_fields
+            .first()
+            .filter(|f| pk_fields.len() == 1 && is_cursor_type(&f.ty))
+        {
+            let pk_ident = &pk.ident;
+            let pk_column = pk.sql_column();
+            let cursor_fields: Vec<&CrudField> = fields
+                .iter()
+                .filter(|f| !f.skip && is_cursor_type(&f.ty))
+                .collect();
+            // (排序中可以使用的名称, SQL 中的列名)
+            let mut cursor_columns = vec![];
+            let mut cursor_arms = vec![];
+            for f in &cursor_fields {
+                let column = f.sql_column();
+                let mut names = vec![column.clone()];
+                for name in [&f.column, &f.name] {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+                let f_ident = &f.ident;
+                cursor_arms
+                    .push(quote!(#(#names)|* => Some(#krate::Val::from(self.#f_ident.clone())),));
+                cursor_columns.extend(names.iter().map(|name| quote!((#name, #column))));
             }
+            tokens.push(quote! {
+                /// 游标中保存的列值, 列不能用于游标分页时为 None
+                pub fn get_cursor_value(&self, column: &str) -> Option<#krate::Val> {
+                    match column {
+                        #(#cursor_arms)*
+                        _ => None,
+                    }
+                }
 
-            /// 依据条件删除记录
-            pub async fn delete_by_cond(pool: &common::types::Db, cond: &common::types::Cond) -> Result<(), &'static str> {
-                let sql_cond = cond.build();
-                let where_str = if cond.has_args() { format!(" WHERE {}", &sql_cond) } else { sql_cond };
-                let mut delete_sql = String::from("DELETE FROM ");
-                delete_sql.push_str(Self::get_table_name());
-                delete_sql.push_str(&where_str);
-                let mut builder = sqlx::query(&delete_sql);
-                for v in &cond.args {
-                    match v {
-                        common::types::Val::I8(rv) =>   { builder = builder.bind::<i8>(*rv);        },
-                        common::types::Val::U8(rv) =>   { builder = builder.bind::<i8>(*rv as i8);  },
-                        common::types::Val::I16(rv) =>  { builder = builder.bind::<i16>(*rv);       },
-                        common::types::Val::U16(rv) =>  { builder = builder.bind::<i16>(*rv as i16);},
-                        common::types::Val::I32(rv) =>  { builder = builder.bind::<i32>(*rv);       },
-                        common::types::Val::U32(rv) =>  { builder = builder.bind::<i32>(*rv as i32);},
-                        common::types::Val::I64(rv) =>  { builder = builder.bind::<i64>(*rv);       },
-                        common::types::Val::U64(rv) =>  { builder = builder.bind::<i64>(*rv as i64);},
-                        common::types::Val::F32(rv) =>  { builder = builder.bind::<f32>(*rv);       },
-                        common::types::Val::F64(rv) =>  { builder = builder.bind::<f64>(*rv);       },
-                        common::types::Val::Str(rv) =>  { builder = builder.bind(rv);               },
-                        common::types::Val::S(rv) =>    { builder = builder.bind(rv);               },
-                        common::types::Val::Bool(rv) => { builder = builder.bind(rv);               },
-                        _ => { continue; }
-                    };
+                /// get_page_after: 游标分页, 依据 cond 的条件和排序 (单列, 相同时按主键) 获取 cursor 之后 (或之前) 的 limit 条记录
+                /// (限制在 1 到 `MAX_PAGE_LIMIT` 之间); 不使用 OFFSET, 只有 `cond.with_total()` 时才统计总数
+                pub fn get_page_after<'a, A>(db: A, cond: &'a #krate::Cond, cursor: Option<&'a #krate::Cursor>, limit: i64) -> impl std::future::Future<Output = Result<#krate::Page<Self>, #krate::CrudError>> + Send + 'a
+                where
+                    A: sqlx::Acquire<'a, Database = #db> + Send + 'a,
+                {
+                    async move {
+                        let mut conn = db.acquire().await?;
+                        let limit = #krate::keyset_limit(limit);
+                        let cursor_columns: &[(&str, &str)] = &[#(#cursor_columns),*];
+                        // 排序列转换为带引号的列名, 按主键排序时只使用主键
+                        let sort = match #krate::keyset_order(cond.get_order_by())? {
+                            Some((c, desc)) => match cursor_columns.iter().find(|(name, _)| *name == c) {
+                                Some((_, column)) => Some((*column, desc)),
+                                None => return Err(#krate::CrudError::Validation(format!("列 {} 不能用于游标分页", c))),
+                            },
+                            None => None,
+                        };
+                        let desc = sort.map(|(_, d)| d).unwrap_or(false);
+                        let sort = sort.filter(|(c, _)| *c != #pk_column);
+                        let mut columns: Vec<&str> = sort.iter().map(|(c, _)| *c).collect();
+                        columns.push(#pk_column);
+                        let (keyset_where, order_by) = #krate::keyset_sql(#dialect, &columns, desc, cursor, cond.arg_count())?;
+                        cond.check()?;
+                        let sql_cond = #build_cond;
+                        let wheres: Vec<String> = [(!sql_cond.is_empty()).then(|| format!("({})", sql_cond)), keyset_where].into_iter().flatten().collect();
+                        let where_str = if wheres.is_empty() { String::new() } else { format!(" WHERE {}", wheres.join(" AND ")) };
+                        let sql = format!("SELECT {} FROM {}{} ORDER BY {} LIMIT {}", Self::get_fields(), Self::get_table_name(), where_str, order_by, limit + 1);
+                        #krate::log::info!("SQL: {}", &sql);
+                        let mut builder = sqlx::query_as::<_, Self>(&sql);
+                        let keys = cursor.map(|c| c.values()).unwrap_or_default();
+                        builder = #krate::BindVal::bind_vals(builder, cond.args.iter().chain(&keys))?;
+                        let mut rows = match builder.fetch_all(&mut *conn).await {
+                            Ok(v) => v,
+                            Err(err) => {
+                                #krate::log::error!("游标分页获取数据失败: {:?},\nSQL: {}", err, sql);
+                                return Err(err.into());
+                            }
+                        };
+                        let has_more = rows.len() as i64 > limit;
+                        rows.truncate(limit as usize);
+                        let before = cursor.map(|c| c.is_before()).unwrap_or(false);
+                        if before {
+                            rows.reverse();
+                        }
+                        let keys_of = |row: &Self| {
+                            let mut keys: Vec<#krate::Val> = sort.and_then(|(c, _)| row.get_cursor_value(c)).into_iter().collect();
+                            keys.push(#krate::Val::from(row.#pk_ident.clone()));
+                            keys
+                        };
+                        let (next, prev) = match (rows.first(), rows.last()) {
+                            (Some(first), Some(last)) => (
+                                (before || has_more).then(|| #krate::Cursor::after(keys_of(last))).transpose()?,
+                                (if before { has_more } else { cursor.is_some() }).then(|| #krate::Cursor::before(keys_of(first))).transpose()?,
+                            ),
+                            _ => (None, None),
+                        };
+                        let total = if cond.need_total() { Some(Self::count_by_cond(&mut *conn, cond).await?) } else { None };
+                        Ok(#krate::Page { rows, next, prev, total })
+                    }
+                }
+            });
+        }
+        if let (false, Some((_, dead, _, restore))) = (pk_fields.is_empty(), &soft_delete) {
+            let restore_sql = format!("SET {} WHERE ", restore);
+            let dead_and = format!(" AND {}", dead);
+            tokens.push(quote! {
+                /// 恢复软删除的记录
+                pub async fn restore<'e, E>(&self, executor: E) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let sql = format!("UPDATE {} {}{}{}", Self::get_table_name(), #restore_sql, #pk_where, #dead_and);
+                    let mut builder = sqlx::query(&sql);
+                    #(#pk_self_binds)*
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#krate::CrudError::NotFound);
+                    }
+                    Ok(())
                 }
-                match builder.execute(pool).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        println!("依据条件删除记录失败: {}", e);
-                        Err("依据条件删除记录失败")
+
+                /// 物理删除记录 (不经过软删除)
+                #force_delete_vis async fn #force_delete_fn<'e, E>(&self, executor: E) -> Result<(), #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let mut index = 1;
+                    let sql = format!("DELETE FROM {} WHERE {}", Self::get_table_name(), #pk_where);
+                    let mut builder = sqlx::query(&sql);
+                    #(#pk_self_binds)*
+                    let result = builder.execute(executor).await?;
+                    if result.rows_affected() == 0 {
+                        return Err(#krate::CrudError::NotFound);
                     }
+                    Ok(())
                 }
+            });
+        }
+        tokens.push(quote! {
+
+            /// 依据条件删除记录
+            pub async fn delete_by_cond<'e, E>(executor: E, cond: &#krate::Cond) -> Result<(), #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                cond.check()?;
+                let sql_cond = #build_cond;
+                let where_str = if !sql_cond.is_empty() { format!(" WHERE {}", &sql_cond) } else { sql_cond };
+                let mut delete_sql = #delete_head;
+                delete_sql.push_str(&where_str);
+                let mut builder = sqlx::query(&delete_sql);
+                builder = #krate::BindVal::bind_vals(builder, &cond.args)?;
+                builder.execute(executor).await?;
+                Ok(())
             }
         });
     }
 
+    if hooks {
+        let self_ty = quote!(Self);
+        tokens.push(expand_hooked(
+            backend,
+            &krate,
+            Hook::Create,
+            "create",
+            "创建记录",
+            &[],
+            quote!(()),
+        ));
+        tokens.push(expand_hooked(
+            backend,
+            &krate,
+            Hook::Create,
+            "create_or_skip_by",
+            "创建记录, 冲突时跳过; 返回是否插入了记录",
+            &[("skip_field", quote!(#column_ident))],
+            quote!(bool),
+        ));
+        if backend != Backend::MySql || id_field.is_some() {
+            tokens.push(expand_hooked(
+                backend,
+                &krate,
+                Hook::Create,
+                "create_returning",
+                "创建记录并返回插入后的记录 (包括数据库生成的 id / 默认值)",
+                &[],
+                self_ty,
+            ));
+        }
+        if let Some(id_field) = id_field {
+            let id_type = &id_field.ty;
+            tokens.push(expand_hooked(
+                backend,
+                &krate,
+                Hook::Create,
+                "create_returning_id",
+                "创建记录并返回 id",
+                &[],
+                quote!(#id_type),
+            ));
+        }
+        if !pk_fields.is_empty() {
+            tokens.push(expand_hooked(
+                backend,
+                &krate,
+                Hook::Save,
+                "update",
+                "更新记录 - 修改指定字段",
+                &[("cond_fields", quote!(&[(#column_ident, #krate::Val)]))],
+                quote!(()),
+            ));
+            tokens.push(expand_hooked(
+                backend,
+                &krate,
+                Hook::Save,
+                "save",
+                "更新记录 - 修改所有字段",
+                &[],
+                quote!(()),
+            ));
+            tokens.push(expand_hooked(
+                backend,
+                &krate,
+                Hook::Delete,
+                "delete",
+                "删除记录",
+                &[],
+                quote!(()),
+            ));
+            if soft_delete.is_some() {
+                tokens.push(expand_hooked(
+                    backend,
+                    &krate,
+                    Hook::Delete,
+                    "force_delete",
+                    "物理删除记录 (不经过软删除)",
+                    &[],
+                    quote!(()),
+                ));
+            }
+        }
+    }
+
+    // 列枚举
+    let column_variants: Vec<Ident> = fields
+        .iter()
+        .filter(|f| !f.skip)
+        .map(|f| Ident::new(&pascalcase::to_pascal_case(&f.name), f.ident.span()))
+        .collect();
+    let column_names: Vec<String> = fields
+        .iter()
+        .filter(|f| !f.skip)
+        .map(|f| f.sql_column())
+        .collect();
+    // from_name 接受字段名、列名以及带引号的列名
+    let column_aliases: Vec<Vec<String>> = fields
+        .iter()
+        .filter(|f| !f.skip)
+        .map(|f| {
+            let mut names = vec![f.name.clone()];
+            for name in [f.column.clone(), f.sql_column()] {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            names
+        })
+        .collect();
+    let column_doc = format!("{} 的列", input.ident);
+    // 类型化的列: `User::columns().age.gt(18)`
+    let columns_ident = format_ident!("{}Columns", input.ident);
+    let columns_doc = format!(
+        "{} 的类型化的列, 用于构造 `Cond::filter` 的条件",
+        input.ident
+    );
+    let typed_fields: Vec<&CrudField> = fields.iter().filter(|f| !f.skip).collect();
+    let typed_idents = typed_fields.iter().map(|f| &f.ident);
+    let typed_decls = typed_fields.iter().map(|f| {
+        let (vis, ident, ty) = (&f.vis, &f.ident, f.value_type());
+        quote!(#vis #ident: #krate::Col<#ty>)
+    });
+
+    tokens.push(ddl::expand_create_table(
+        backend, &krate, &table, &fields, &pk_fields,
+    ));
+    tokens.push(ddl::expand_verify_schema(
+        backend,
+        &krate,
+        container.schema.as_deref(),
+        &table,
+        &fields,
+    ));
+    tokens.extend(relation::expand_relations(
+        &input.ident,
+        &container,
+        &fields,
+        &pk_fields,
+        &krate,
+    )?);
+
     // 生成代码
-    let DeriveInput { ident, .. } = parse_macro_input!(input_clone2);
-    let expanded = quote! {
+    let ident = &input.ident;
+    let vis = &input.vis;
+    Ok(quote! {
         impl #ident {
             #(#tokens)*
+
+            /// 类型化的列: `User::columns().age.gt(18).and(User::columns().name.ilike("%a%"))`
+            pub const fn columns() -> #columns_ident {
+                #columns_ident {
+                    #(#typed_idents: #krate::Col::new(#column_names),)*
+                }
+            }
         }
-    };
 
-    expanded.into() // 将代码转换为 token 流并返回
+        #[doc = #columns_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #columns_ident {
+            #(#typed_decls,)*
+        }
+
+        #[doc = #column_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #vis enum #column_ident {
+            #(#column_variants,)*
+        }
+
+        impl #column_ident {
+            /// SQL 中使用的列名 (必要时带引号)
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    #(Self::#column_variants => #column_names,)*
+                }
+            }
+
+            /// 依据字段名或列名查找列, 用于校验来自外部的列名 (例如排序参数)
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#(#column_aliases)|* => Some(Self::#column_variants),)*
+                    _ => None,
+                }
+            }
+        }
+    })
 }
@@ -0,0 +1,233 @@
+//! 关联关系: belongs_to / has_many / many_to_many
+
+use crate::attr::{ContainerAttrs, CrudField};
+use crate::backend::Backend;
+use inflector::cases::snakecase;
+use inflector::string::pluralize;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::Path;
+
+/// `column IN (...)` 条件及绑定: PostgreSQL 使用 `= ANY($1)` 绑定数组, 其他数据库逐个绑定
+///
+/// 生成的代码中 `ids` 为 `Vec<#ty>`, `builder` 为查询; 返回 (条件表达式, 绑定语句, 额外的 where 约束)
+pub(crate) fn in_list(
+    backend: Backend,
+    krate: &Path,
+    column: &str,
+    ty: &TokenStream,
+) -> (TokenStream, TokenStream, TokenStream) {
+    if backend == Backend::Postgres {
+        let sql = format!("{} = ANY($1)", column);
+        (
+            quote!(String::from(#sql)),
+            quote!(builder = builder.bind(&ids[..]);),
+            // 写成 for<'r> 的约束, 类型不支持数组时只在调用时报错
+            quote!(for<'r> &'r [#ty]: sqlx::Encode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,),
+        )
+    } else {
+        let dialect = backend.dialect(krate);
+        (
+            quote! {
+                format!(
+                    "{} IN ({})",
+                    #column,
+                    (1..=ids.len()).map(|i| #dialect.placeholder(i)).collect::<Vec<_>>().join(",")
+                )
+            },
+            quote! {
+                for id in ids.iter() {
+                    builder = builder.bind(id);
+                }
+            },
+            quote!(),
+        )
+    }
+}
+
+/// 方法名称: `Order` -> `orders`
+fn plural_method(target: &Path) -> String {
+    let name = target
+        .segments
+        .last()
+        .map(|s| s.ident.to_string())
+        .unwrap_or_default();
+    pluralize::to_plural(&snakecase::to_snake_case(&name))
+}
+
+/// `schema.table` 每一段分别加引号
+fn quote_table(backend: Backend, name: &str) -> String {
+    name.split('.')
+        .map(|part| backend.quote_ident(part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// 生成关联方法
+pub(crate) fn expand_relations(
+    ident: &Ident,
+    container: &ContainerAttrs,
+    fields: &[CrudField],
+    pk_fields: &[&CrudField],
+    krate: &Path,
+) -> syn::Result<Vec<TokenStream>> {
+    let backend = container.backend;
+    let db = backend.db_type();
+    let p1 = backend.placeholder(1);
+    let own_key = format!("{}_id", snakecase::to_snake_case(&ident.to_string()));
+    let mut tokens = vec![];
+
+    // belongs_to: 外键字段 -> 对方记录
+    for field in fields.iter().filter(|f| !f.skip) {
+        let Some(target) = &field.belongs_to else {
+            continue;
+        };
+        let field_ident = &field.ident;
+        let name = field
+            .name
+            .strip_suffix("_id")
+            .filter(|n| !n.is_empty())
+            .map(str::to_owned)
+            .unwrap_or_else(|| plural_method(target));
+        let method = Ident::new(&name, field.ident.span());
+        let load_method = Ident::new(
+            &format!("load_{}", pluralize::to_plural(&name)),
+            field.ident.span(),
+        );
+        let doc = format!("依据 {} 获取关联的记录 (belongs_to)", field.name);
+        let load_doc = format!(
+            "批量获取 {} 关联的记录, 一次查询避免 N+1; 返回的顺序不固定",
+            field.name
+        );
+        if field.is_option() {
+            tokens.push(quote! {
+                #[doc = #doc]
+                pub async fn #method<'e, E>(&self, executor: E) -> Result<Option<#target>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    match &self.#field_ident {
+                        Some(v) => #target::find_by_pk(executor, v.clone()).await.map(Some),
+                        None => Ok(None),
+                    }
+                }
+
+                #[doc = #load_doc]
+                pub async fn #load_method<'e, E>(executor: E, rows: &[Self]) -> Result<Vec<#target>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let ids: Vec<_> = rows.iter().filter_map(|r| r.#field_ident.clone()).collect();
+                    #target::find_by_pks(executor, &ids).await
+                }
+            });
+        } else {
+            tokens.push(quote! {
+                #[doc = #doc]
+                pub async fn #method<'e, E>(&self, executor: E) -> Result<#target, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    #target::find_by_pk(executor, self.#field_ident.clone()).await
+                }
+
+                #[doc = #load_doc]
+                pub async fn #load_method<'e, E>(executor: E, rows: &[Self]) -> Result<Vec<#target>, #krate::CrudError>
+                where
+                    E: sqlx::Executor<'e, Database = #db>,
+                {
+                    let ids: Vec<_> = rows.iter().map(|r| r.#field_ident.clone()).collect();
+                    #target::find_by_pks(executor, &ids).await
+                }
+            });
+        }
+    }
+
+    if container.has_many.is_empty() && container.many_to_many.is_empty() {
+        return Ok(tokens);
+    }
+    // has_many / many_to_many 依据本表的主键查询
+    let [pk] = pk_fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "has_many / many_to_many 需要单一主键",
+        ));
+    };
+    let pk_ident = &pk.ident;
+    let pk_value = pk.bind_value();
+    let pk_ty = &pk.ty;
+
+    for rel in &container.has_many {
+        let target = &rel.target;
+        let name = plural_method(target);
+        let method = Ident::new(&name, Span::call_site());
+        let load_method = Ident::new(&format!("load_{}", name), Span::call_site());
+        let foreign_key = backend.quote_ident(rel.foreign_key.as_deref().unwrap_or(&own_key));
+        let one_where = format!("{} = {}", foreign_key, p1);
+        let (in_where, in_binds, in_bounds) =
+            in_list(backend, krate, &foreign_key, &quote!(#pk_ty));
+        tokens.push(quote! {
+            /// 获取关联的全部记录 (has_many)
+            pub async fn #method<'e, E>(&self, executor: E) -> Result<Vec<#target>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                let alive = #target::get_alive_cond();
+                let sql = format!("SELECT {} FROM {} WHERE {}{}{}", #target::get_fields(), #target::get_table_name(), #one_where, if alive.is_empty() { "" } else { " AND " }, alive);
+                sqlx::query_as::<_, #target>(&sql).bind(#pk_value).fetch_all(executor).await.map_err(#krate::CrudError::from)
+            }
+
+            /// 批量获取多条记录关联的全部记录 (has_many), 一次查询避免 N+1
+            pub async fn #load_method<'e, E>(executor: E, rows: &[Self]) -> Result<Vec<#target>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+                #in_bounds
+            {
+                let ids: Vec<#pk_ty> = rows.iter().map(|r| r.#pk_ident.clone()).collect();
+                if ids.is_empty() {
+                    return Ok(vec![]);
+                }
+                let alive = #target::get_alive_cond();
+                let sql = format!("SELECT {} FROM {} WHERE {}{}{}", #target::get_fields(), #target::get_table_name(), #in_where, if alive.is_empty() { "" } else { " AND " }, alive);
+                let mut builder = sqlx::query_as::<_, #target>(&sql);
+                #in_binds
+                builder.fetch_all(executor).await.map_err(#krate::CrudError::from)
+            }
+        });
+    }
+
+    for rel in &container.many_to_many {
+        let target = &rel.target;
+        let method = Ident::new(&plural_method(target), Span::call_site());
+        let target_name = target
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default();
+        let related_key = backend.quote_ident(
+            &rel.related_key
+                .clone()
+                .unwrap_or_else(|| format!("{}_id", snakecase::to_snake_case(&target_name))),
+        );
+        let foreign_key = backend.quote_ident(rel.foreign_key.as_deref().unwrap_or(&own_key));
+        let sub_query = format!(
+            "SELECT {} FROM {} WHERE {} = {}",
+            related_key,
+            quote_table(backend, &rel.join_table),
+            foreign_key,
+            p1
+        );
+        tokens.push(quote! {
+            /// 通过中间表获取关联的全部记录 (many_to_many)
+            pub async fn #method<'e, E>(&self, executor: E) -> Result<Vec<#target>, #krate::CrudError>
+            where
+                E: sqlx::Executor<'e, Database = #db>,
+            {
+                let alive = #target::get_alive_cond();
+                let sql = format!("SELECT {} FROM {} WHERE {} IN ({}){}{}", #target::get_fields(), #target::get_table_name(), #target::get_pk_column(), #sub_query, if alive.is_empty() { "" } else { " AND " }, alive);
+                sqlx::query_as::<_, #target>(&sql).bind(#pk_value).fetch_all(executor).await.map_err(#krate::CrudError::from)
+            }
+        });
+    }
+    Ok(tokens)
+}
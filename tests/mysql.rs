@@ -0,0 +1,30 @@
+//! MySQL 生成的 SQL (不连接数据库)
+
+use crud_derive::CRUDTable;
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "mysql")]
+pub struct Order {
+    pub id: u64,
+    pub amount: i32,
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "mysql")]
+pub struct Country {
+    #[crud(primary_key)]
+    pub id: String,
+    pub name: String,
+}
+
+#[test]
+fn unsigned_id_is_auto_increment() {
+    let sql = Order::create_table_sql();
+    assert!(
+        sql.contains("`id` BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY"),
+        "{}",
+        sql
+    );
+    assert!(!Order::get_insert_fields().contains("`id`"));
+    assert!(Country::get_insert_fields().contains("`id`"));
+}
@@ -0,0 +1,1296 @@
+use crud_derive::CRUDTable;
+use crud_runtime::{Cond, CrudError, UpsertUpdate};
+use sqlx::SqlitePool;
+
+async fn pool(tables: &[String]) -> SqlitePool {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    for sql in tables {
+        sqlx::raw_sql(sql).execute(&pool).await.unwrap();
+    }
+    pool
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", table = "people", schema = "main")]
+pub struct Person {
+    #[crud(column = "personId")]
+    pub id: i64,
+    pub name: String,
+    #[crud(readonly, default = "7")]
+    pub score: i32,
+    #[crud(skip)]
+    #[sqlx(skip)]
+    pub cache: Vec<u8>,
+}
+
+#[tokio::test]
+async fn container_and_field_attrs() {
+    assert_eq!(Person::get_table_name(), "\"main\".\"people\"");
+    let pool = pool(&[Person::create_table_sql()]).await;
+    let person = Person {
+        name: "a".into(),
+        score: 100,
+        cache: vec![1],
+        ..Default::default()
+    };
+    person.create(&pool).await.unwrap();
+    let mut got = Person::get_by_name(&pool, &"a".to_owned()).await.unwrap();
+    // readonly 不写入, 使用数据库的默认值; skip 不读取
+    assert_eq!((got.id, got.score, got.cache.len()), (1, 7, 0));
+    got.score = 100;
+    got.name = "b".into();
+    got.save(&pool).await.unwrap();
+    let got = Person::find_by_pk(&pool, 1).await.unwrap();
+    assert_eq!((got.name.as_str(), got.score), ("b", 7));
+    let id: i64 = sqlx::query_scalar("SELECT \"personId\" FROM people")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(id, 1);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Account {
+    pub id: i64,
+    #[crud(unique)]
+    pub email: String,
+}
+
+#[tokio::test]
+async fn typed_errors() {
+    let pool = pool(&[
+        Account::create_table_sql(),
+        "CREATE TABLE children (id INTEGER PRIMARY KEY, account_id INTEGER NOT NULL REFERENCES accounts (id))".to_owned(),
+    ])
+    .await;
+    assert!(matches!(
+        Account::find_by_pk(&pool, 1).await,
+        Err(CrudError::NotFound)
+    ));
+    let account = Account {
+        email: "a@x.com".into(),
+        ..Default::default()
+    };
+    account.create(&pool).await.unwrap();
+    assert!(matches!(
+        account.create(&pool).await,
+        Err(CrudError::UniqueViolation { .. })
+    ));
+    let missing = Account {
+        id: 9,
+        ..Default::default()
+    };
+    assert!(matches!(
+        missing.save(&pool).await,
+        Err(CrudError::NotFound)
+    ));
+    assert!(matches!(
+        missing.delete(&pool).await,
+        Err(CrudError::NotFound)
+    ));
+    let err = sqlx::query("INSERT INTO children (account_id) VALUES (9)")
+        .execute(&pool)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        CrudError::from(err),
+        CrudError::ForeignKeyViolation { .. }
+    ));
+}
+
+#[tokio::test]
+async fn runs_inside_transactions() {
+    let pool = pool(&[Account::create_table_sql()]).await;
+    let account = Account {
+        email: "a@x.com".into(),
+        ..Default::default()
+    };
+    let mut tx = pool.begin().await.unwrap();
+    account.create(&mut *tx).await.unwrap();
+    let (rows, total) = Account::get_all_by_cond(&mut *tx, &Cond::new())
+        .await
+        .unwrap();
+    assert_eq!((rows.len(), total), (1, 1));
+    tx.rollback().await.unwrap();
+    assert!(Account::get_all(&pool).await.unwrap().is_empty());
+
+    let mut tx = pool.begin().await.unwrap();
+    account.create(&mut *tx).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(Account::get_all(&pool).await.unwrap().len(), 1);
+    // OFFSET 饱和而不是溢出
+    let (rows, total) = Account::get_all_by_cond(&pool, &Cond::new().page(i64::MAX, i64::MAX))
+        .await
+        .unwrap();
+    assert_eq!((rows.len(), total), (0, 1));
+}
+
+#[tokio::test]
+async fn raw_placeholders() {
+    let pool = pool(&[Account::create_table_sql()]).await;
+    Account {
+        email: "?".into(),
+        ..Default::default()
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+    let literal = Cond::new().raw("email = '?'", []);
+    assert_eq!(Account::count_by_cond(&pool, &literal).await.unwrap(), 1);
+    let quoted = Cond::new()
+        .raw("email = ?", ["?".into()])
+        .raw("instr(email, '??') = 0", []);
+    assert_eq!(Account::count_by_cond(&pool, &quoted).await.unwrap(), 1);
+    // 参数个数与占位符不一致
+    let missing = Cond::new().raw("email = ?", []);
+    assert!(matches!(
+        Account::count_by_cond(&pool, &missing).await,
+        Err(CrudError::Validation(_))
+    ));
+    assert!(matches!(
+        Account::get_all_by_cond(&pool, &missing).await,
+        Err(CrudError::Validation(_))
+    ));
+    assert!(Account::get_by_cond(&pool, &missing).await.is_none());
+    let extra = Cond::new().raw("email = ?", ["?".into(), "x".into()]);
+    assert!(matches!(
+        Account::delete_by_cond(&pool, &extra).await,
+        Err(CrudError::Validation(_))
+    ));
+    assert_eq!(Account::get_all(&pool).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn create_returning() {
+    let pool = pool(&[Person::create_table_sql(), Account::create_table_sql()]).await;
+    let person = Person {
+        name: "a".into(),
+        ..Default::default()
+    };
+    let row = person.create_returning(&pool).await.unwrap();
+    assert_eq!((row.id, row.name.as_str(), row.score), (1, "a", 7));
+    assert_eq!(person.create_returning_id(&pool).await.unwrap(), 2);
+
+    let account = Account {
+        email: "a@x.com".into(),
+        ..Default::default()
+    };
+    assert!(account
+        .create_or_skip_by(&pool, AccountColumn::Email)
+        .await
+        .unwrap());
+    assert!(!account
+        .create_or_skip_by(&pool, AccountColumn::Email)
+        .await
+        .unwrap());
+    assert_eq!(Account::get_all(&pool).await.unwrap().len(), 1);
+}
+
+/// 列名为 SQL 关键字
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", table = "groups")]
+pub struct Group {
+    pub id: i64,
+    pub order: i32,
+    pub user: String,
+    pub desc: Option<String>,
+}
+
+#[tokio::test]
+async fn reserved_word_columns() {
+    let pool = pool(&[Group::create_table_sql()]).await;
+    for i in 0..5 {
+        Group {
+            order: 5 - i,
+            user: format!("u{}", i),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+    let (rows, total) = Group::get_all_by_cond(&pool, &Cond::new().order_by("order DESC"))
+        .await
+        .unwrap();
+    assert_eq!((rows[0].order, total), (5, 5));
+    let mut group = rows[0].clone();
+    group.desc = Some("d".into());
+    group.save(&pool).await.unwrap();
+    let got = Group::get_by_user(&pool, &"u0".to_owned()).await.unwrap();
+    assert_eq!(got.desc.as_deref(), Some("d"));
+    Group::verify_schema(&pool).await.unwrap();
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Sample {
+    pub id: i64,
+    pub small: i16,
+    pub ratio: f64,
+    pub active: bool,
+    pub label: String,
+    pub data: Vec<u8>,
+    pub created: i64,
+    pub updated: i64,
+}
+
+#[tokio::test]
+async fn persists_every_field_type() {
+    let pool = pool(&[Sample::create_table_sql()]).await;
+    let sample = Sample {
+        small: -3,
+        ratio: 0.25,
+        active: true,
+        label: String::new(),
+        data: vec![0, 255],
+        ..Default::default()
+    };
+    let mut row = sample.create_returning(&pool).await.unwrap();
+    assert_eq!(
+        (
+            row.small,
+            row.ratio,
+            row.active,
+            row.label.as_str(),
+            &row.data[..]
+        ),
+        (-3, 0.25, true, "", &[0, 255][..])
+    );
+    assert!(row.created > 0 && row.updated > 0);
+    row.ratio = 1.5;
+    row.active = false;
+    row.label = "x".into();
+    row.data.clear();
+    row.save(&pool).await.unwrap();
+    let got = Sample::find_by_pk(&pool, row.id).await.unwrap();
+    assert_eq!(
+        (got.ratio, got.active, got.label.as_str(), got.data.len()),
+        (1.5, false, "x", 0)
+    );
+    assert_eq!(got.created, row.created);
+    assert!(got.updated >= row.updated);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Memo {
+    pub id: i64,
+    pub title: String,
+    #[crud(default = "'n/a'")]
+    pub note: Option<String>,
+}
+
+#[tokio::test]
+async fn option_fields_map_to_null() {
+    let pool = pool(&[Memo::create_table_sql()]).await;
+    // create: None 使用数据库的默认值
+    let mut memo = Memo {
+        title: "a".into(),
+        ..Default::default()
+    }
+    .create_returning(&pool)
+    .await
+    .unwrap();
+    assert_eq!(memo.note.as_deref(), Some("n/a"));
+    // save: None 写入 NULL
+    memo.note = None;
+    memo.save(&pool).await.unwrap();
+    assert_eq!(Memo::find_by_pk(&pool, memo.id).await.unwrap().note, None);
+    Memo {
+        title: "b".into(),
+        note: Some("x".into()),
+        ..Default::default()
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+    // &None 查询 IS NULL
+    let rows = Memo::get_all_by_note(&pool, &None).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].title, "a");
+    let rows = Memo::get_all_by_note(&pool, &Some("x".into()))
+        .await
+        .unwrap();
+    assert_eq!(rows[0].title, "b");
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Ticket {
+    pub id: u32,
+    pub code: String,
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Note {
+    #[crud(primary_key)]
+    pub id: String,
+    #[crud(unique)]
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Stock {
+    #[crud(primary_key)]
+    pub tenant_id: i64,
+    #[crud(primary_key)]
+    pub sku: String,
+    pub qty: i32,
+}
+
+#[tokio::test]
+async fn primary_keys() {
+    let pool = pool(&[
+        Ticket::create_table_sql(),
+        Note::create_table_sql(),
+        Stock::create_table_sql(),
+    ])
+    .await;
+    // 无符号整数的 id 也由数据库生成
+    let ticket = Ticket {
+        code: "a".into(),
+        ..Default::default()
+    };
+    assert_eq!(ticket.create_returning_id(&pool).await.unwrap(), 1);
+    assert_eq!(ticket.create_returning(&pool).await.unwrap().id, 2);
+
+    // String 的 id 由调用方提供
+    let note = Note {
+        id: "n1".into(),
+        title: "t".into(),
+        body: "b".into(),
+    };
+    assert_eq!(note.create_returning_id(&pool).await.unwrap(), "n1");
+    let mut got = Note::find_by_pk(&pool, "n1".to_owned()).await.unwrap();
+    got.body = "saved".into();
+    got.save(&pool).await.unwrap();
+    assert_eq!(
+        Note::get_by_id(&pool, &"n1".to_owned()).await.unwrap().body,
+        "saved"
+    );
+    Note::verify_schema(&pool).await.unwrap();
+
+    // 联合主键
+    let mut stock = Stock {
+        tenant_id: 1,
+        sku: "x".into(),
+        qty: 2,
+    };
+    stock.create(&pool).await.unwrap();
+    stock.qty = 5;
+    stock.save(&pool).await.unwrap();
+    let got = Stock::find_by_pk(&pool, (1, "x".to_owned())).await.unwrap();
+    assert_eq!(got.qty, 5);
+    assert!(!Stock::exists_by_pk(&pool, (2, "x".to_owned()))
+        .await
+        .unwrap());
+    Stock::delete_by_pk(&pool, (1, "x".to_owned()))
+        .await
+        .unwrap();
+    assert!(matches!(
+        stock.delete(&pool).await,
+        Err(CrudError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn upsert() {
+    let pool = pool(&[Account::create_table_sql(), Note::create_table_sql()]).await;
+    // 主键为空时使用主键作为冲突目标
+    let mut note = Note {
+        id: "n1".into(),
+        title: "t".into(),
+        body: "a".into(),
+    };
+    note.upsert(&pool, &[], UpsertUpdate::All).await.unwrap();
+    note.body = "b".into();
+    assert_eq!(
+        note.upsert(&pool, &[], UpsertUpdate::All)
+            .await
+            .unwrap()
+            .body,
+        "b"
+    );
+
+    // 按唯一列冲突, 只更新指定的列
+    let other = Note {
+        id: "n2".into(),
+        title: "t".into(),
+        body: "c".into(),
+    };
+    let got = other
+        .upsert(
+            &pool,
+            &[NoteColumn::Title],
+            UpsertUpdate::Columns(vec![NoteColumn::Body]),
+        )
+        .await
+        .unwrap();
+    assert_eq!((got.id.as_str(), got.body.as_str()), ("n1", "c"));
+    let got = other
+        .upsert(
+            &pool,
+            &[NoteColumn::Title],
+            UpsertUpdate::Exprs(vec![(
+                NoteColumn::Body,
+                "notes.body || EXCLUDED.body".into(),
+            )]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(got.body, "cc");
+
+    // 数据库生成的 id 不能作为默认的冲突目标
+    let account = Account {
+        email: "a@x.com".into(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        account.upsert(&pool, &[], UpsertUpdate::All).await,
+        Err(CrudError::Validation(_))
+    ));
+    let first = account
+        .upsert(&pool, &[AccountColumn::Email], UpsertUpdate::All)
+        .await
+        .unwrap();
+    let again = account
+        .upsert(&pool, &[AccountColumn::Email], UpsertUpdate::All)
+        .await
+        .unwrap();
+    assert_eq!(first.id, again.id);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", table = "customers", soft_delete = "deleted_at")]
+pub struct Customer {
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: Option<i64>,
+    pub created: i64,
+    pub updated: i64,
+}
+
+#[tokio::test]
+async fn soft_delete() {
+    let pool = pool(&[Customer::create_table_sql()]).await;
+    for name in ["a", "b", "c"] {
+        Customer {
+            name: name.into(),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+    let a = Customer::get_by_name(&pool, &"a".to_owned()).await.unwrap();
+    a.delete(&pool).await.unwrap();
+    assert!(matches!(a.delete(&pool).await, Err(CrudError::NotFound)));
+    assert!(!Customer::exists_by_pk(&pool, a.id).await.unwrap());
+    assert_eq!(Customer::get_all(&pool).await.unwrap().len(), 2);
+    assert_eq!(
+        Customer::count_by_cond(&pool, &Cond::new().with_deleted())
+            .await
+            .unwrap(),
+        3
+    );
+    // OR 条件不能绕过软删除的过滤
+    let cond = Cond::new().raw("name = ? OR name = ?", ["a".into(), "b".into()]);
+    let (rows, total) = Customer::get_all_by_cond(&pool, &cond).await.unwrap();
+    assert_eq!((rows.len(), total), (1, 1));
+    let (rows, _) = Customer::get_all_by_cond(&pool, &cond.clone().only_deleted())
+        .await
+        .unwrap();
+    assert!(rows[0].deleted_at.is_some());
+    a.restore(&pool).await.unwrap();
+    assert_eq!(Customer::get_all(&pool).await.unwrap().len(), 3);
+    a.force_delete(&pool).await.unwrap();
+    assert_eq!(
+        Customer::count_by_cond(&pool, &Cond::new().with_deleted())
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn create_many() {
+    let pool = pool(&[Account::create_table_sql(), Memo::create_table_sql()]).await;
+    assert_eq!(Account::create_many(&pool, &[]).await.unwrap(), 0);
+
+    // 超过 SQLite 的绑定参数上限时分批执行
+    let accounts: Vec<Account> = (0..40_000)
+        .map(|i| Account {
+            email: format!("{}@x.com", i),
+            ..Default::default()
+        })
+        .collect();
+    assert_eq!(
+        Account::create_many(&pool, &accounts).await.unwrap(),
+        40_000
+    );
+    assert_eq!(
+        Account::count_by_cond(&pool, &Cond::new()).await.unwrap(),
+        40_000
+    );
+
+    // 事务中失败时全部回滚
+    let mut tx = pool.begin().await.unwrap();
+    let rows = [
+        Account {
+            email: "new@x.com".into(),
+            ..Default::default()
+        },
+        Account {
+            email: "0@x.com".into(),
+            ..Default::default()
+        },
+    ];
+    assert!(matches!(
+        Account::create_many(&mut *tx, &rows).await,
+        Err(CrudError::UniqueViolation { .. })
+    ));
+    tx.rollback().await.unwrap();
+    assert_eq!(
+        Account::count_by_cond(&pool, &Cond::new().eq("email", "new@x.com"))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // 与 create 不同, None 写入 NULL
+    let memos = [Memo {
+        title: "a".into(),
+        note: None,
+        ..Default::default()
+    }];
+    Memo::create_many(&pool, &memos).await.unwrap();
+    assert_eq!(
+        Memo::get_by_title(&pool, &"a".to_owned())
+            .await
+            .unwrap()
+            .note,
+        None
+    );
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", table = "docs")]
+pub struct Doc {
+    pub id: i64,
+    #[crud(unique)]
+    pub title: String,
+    #[crud(version)]
+    pub version: i32,
+}
+
+#[tokio::test]
+async fn version_conflict() {
+    let pool = pool(&[Doc::create_table_sql()]).await;
+    let doc = Doc {
+        title: "t".into(),
+        ..Default::default()
+    }
+    .create_returning(&pool)
+    .await
+    .unwrap();
+    assert_eq!(doc.version, 0);
+    let (mut a, mut b) = (doc.clone(), doc.clone());
+    a.title = "a".into();
+    a.save(&pool).await.unwrap();
+    b.title = "b".into();
+    assert!(matches!(b.save(&pool).await, Err(CrudError::Conflict)));
+    assert!(matches!(
+        b.update(&pool, &[(DocColumn::Title, "c".into())]).await,
+        Err(CrudError::Conflict)
+    ));
+    let fresh = Doc::find_by_pk(&pool, doc.id).await.unwrap();
+    assert_eq!((fresh.title.as_str(), fresh.version), ("a", 1));
+    fresh
+        .update(&pool, &[(DocColumn::Title, "c".into())])
+        .await
+        .unwrap();
+    let up = Doc {
+        title: "c".into(),
+        ..Default::default()
+    }
+    .upsert(&pool, &[DocColumn::Title], UpsertUpdate::All)
+    .await
+    .unwrap();
+    assert_eq!(up.version, 3);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(
+    backend = "sqlite",
+    has_many(Book),
+    many_to_many(Tag, join_table = "author_tags")
+)]
+pub struct Author {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", soft_delete = "deleted_at")]
+pub struct Book {
+    pub id: i64,
+    #[crud(belongs_to = "Author")]
+    pub author_id: i64,
+    #[crud(belongs_to = "Author")]
+    pub editor_id: Option<i64>,
+    pub title: String,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+}
+
+#[tokio::test]
+async fn relations() {
+    let pool = pool(&[
+        Author::create_table_sql(),
+        Book::create_table_sql(),
+        Tag::create_table_sql(),
+        "CREATE TABLE author_tags (author_id INTEGER NOT NULL, tag_id INTEGER NOT NULL)".into(),
+    ])
+    .await;
+    let mut authors = vec![];
+    for name in ["a", "b", "c"] {
+        authors.push(
+            Author {
+                name: name.into(),
+                ..Default::default()
+            }
+            .create_returning(&pool)
+            .await
+            .unwrap(),
+        );
+    }
+    let (a, b) = (&authors[0], &authors[1]);
+    for (author_id, title) in [(a.id, "a1"), (a.id, "a2"), (b.id, "b1")] {
+        Book {
+            author_id,
+            editor_id: Some(b.id),
+            title: title.into(),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+    let tag = Tag {
+        name: "t".into(),
+        ..Default::default()
+    }
+    .create_returning(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO author_tags VALUES (?, ?)")
+        .bind(a.id)
+        .bind(tag.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let books = a.books(&pool).await.unwrap();
+    assert_eq!(books.len(), 2);
+    assert_eq!(books[0].author(&pool).await.unwrap().name, "a");
+    assert_eq!(books[0].editor(&pool).await.unwrap().unwrap().name, "b");
+    assert_eq!(a.tags(&pool).await.unwrap()[0].name, "t");
+    assert!(b.tags(&pool).await.unwrap().is_empty());
+
+    // 批量加载
+    assert_eq!(Author::load_books(&pool, &authors).await.unwrap().len(), 3);
+    assert!(Author::load_books(&pool, &[]).await.unwrap().is_empty());
+    let owners = Book::load_authors(&pool, &books).await.unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners[0].id, a.id);
+
+    // 已删除的记录不会被加载
+    books[0].delete(&pool).await.unwrap();
+    assert_eq!(a.books(&pool).await.unwrap().len(), 1);
+    assert_eq!(Author::load_books(&pool, &authors).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn keyset_pages() {
+    let pool = pool(&[Group::create_table_sql()]).await;
+    for i in 0..5 {
+        Group {
+            order: 5 - i,
+            user: format!("u{}", i),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+    let cond = Cond::new().order_by("order");
+    let page = Group::get_page_after(&pool, &cond, None, 2).await.unwrap();
+    assert_eq!(page.rows[0].order, 1);
+    let next: crud_runtime::Cursor = page.next.unwrap().to_string().parse().unwrap();
+    let page = Group::get_page_after(&pool, &cond, Some(&next), 2)
+        .await
+        .unwrap();
+    assert_eq!(page.rows[0].order, 3);
+    let page = Group::get_page_after(&pool, &cond, page.prev.as_ref(), 2)
+        .await
+        .unwrap();
+    assert_eq!(page.rows[0].order, 1);
+    let page = Group::get_page_after(&pool, &Cond::new(), None, i64::MAX)
+        .await
+        .unwrap();
+    assert_eq!(page.rows.len(), 5);
+}
+
+#[tokio::test]
+async fn streams() {
+    use futures::TryStreamExt;
+
+    let pool = pool(&[Account::create_table_sql()]).await;
+    let accounts: Vec<Account> = (0..10)
+        .map(|i| Account {
+            email: format!("{}@x.com", i),
+            ..Default::default()
+        })
+        .collect();
+    Account::create_many(&pool, &accounts).await.unwrap();
+
+    let rows: Vec<Account> = Account::stream_all(&pool).try_collect().await.unwrap();
+    assert_eq!(rows.len(), 10);
+    let cond = Cond::new().raw("id > ?", [3.into()]).page(1, 2);
+    let rows: Vec<Account> = Account::stream_by_cond(&pool, &cond)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 7);
+    let bad = Cond::new().raw("id > ?", []);
+    let mut rows = std::pin::pin!(Account::stream_by_cond(&pool, &bad));
+    assert!(matches!(
+        rows.try_next().await,
+        Err(CrudError::Validation(_))
+    ));
+
+    // 按主键顺序分批, 每批最多 batch_size 条
+    let mut batches = vec![];
+    let n = Account::for_each_batch(&pool, &cond, 3, |rows| {
+        batches.push(rows.iter().map(|r| r.id).collect::<Vec<_>>());
+        async { Ok(()) }
+    })
+    .await
+    .unwrap();
+    assert_eq!(n, 7);
+    assert_eq!(batches, [vec![4, 5, 6], vec![7, 8, 9], vec![10]]);
+
+    // 回调返回错误时停止
+    let mut calls = 0;
+    let err = Account::for_each_batch(&pool, &Cond::new(), 3, |_| {
+        calls += 1;
+        async { Err(CrudError::Validation("stop".into())) }
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CrudError::Validation(_)));
+    assert_eq!(calls, 1);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Item {
+    pub id: i64,
+    #[crud(unique)]
+    pub sku: String,
+    #[crud(index, default = "5")]
+    pub qty: Option<i32>,
+    #[crud(sql_type = "TEXT")]
+    pub note: Option<String>,
+}
+
+#[tokio::test]
+async fn migrations() {
+    let dir = std::env::temp_dir().join(format!("crud-sqlite-migrations-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let written = crud_runtime::Migrations::new()
+        .table(Item::get_table_name(), Item::create_table_sql())
+        .write(&dir)
+        .unwrap();
+    assert_eq!(written.len(), 1);
+    let sql = std::fs::read_to_string(&written[0]).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // 迁移文件可以直接执行, 且重复执行不会报错
+    let pool = pool(&[sql.clone(), sql]).await;
+    Item::verify_schema(&pool).await.unwrap();
+    let index: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND tbl_name = 'items' AND sql LIKE '%qty%'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(index, 1);
+    let item = Item {
+        sku: "a".into(),
+        ..Default::default()
+    };
+    assert_eq!(item.create_returning(&pool).await.unwrap().qty, Some(5));
+    assert!(matches!(
+        item.create(&pool).await,
+        Err(CrudError::UniqueViolation { .. })
+    ));
+}
+
+#[tokio::test]
+async fn verify_schema_reports_mismatches() {
+    let pool = pool(&[
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, sku BLOB, qty INTEGER, tenant_id INTEGER NOT NULL)"
+            .into(),
+    ])
+    .await;
+    let Err(CrudError::Schema(report)) = Item::verify_schema(&pool).await else {
+        panic!("表结构不一致时应返回 CrudError::Schema");
+    };
+    let issues = report.issues;
+    assert_eq!(issues.len(), 4, "{:?}", issues);
+    assert!(issues.contains(&crud_runtime::SchemaIssue::MissingColumn {
+        column: "note".into()
+    }));
+    assert!(issues.contains(&crud_runtime::SchemaIssue::Nullable {
+        column: "sku".into()
+    }));
+    assert!(issues.contains(&crud_runtime::SchemaIssue::ExtraRequired {
+        column: "tenant_id".into()
+    }));
+
+    // 表不存在
+    let pool = self::pool(&[]).await;
+    let Err(CrudError::Schema(report)) = Item::verify_schema(&pool).await else {
+        panic!("表不存在时应返回 CrudError::Schema");
+    };
+    assert_eq!(report.issues, [crud_runtime::SchemaIssue::MissingTable]);
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Member {
+    pub id: i64,
+    #[crud(validate(length(min = 1, max = 4)))]
+    pub name: String,
+    #[crud(validate(email))]
+    pub email: Option<String>,
+    #[crud(validate(range(min = 0, max = 150)))]
+    pub age: i32,
+    #[crud(validate(regex = r"^1\d{2}$"))]
+    pub phone: String,
+}
+
+#[tokio::test]
+async fn validation() {
+    let pool = pool(&[Member::create_table_sql()]).await;
+    let mut member = Member {
+        name: "名字太长了".into(),
+        email: Some("a@b".into()),
+        age: 200,
+        phone: "12".into(),
+        ..Default::default()
+    };
+    let Err(CrudError::Invalid(errors)) = member.create(&pool).await else {
+        panic!("校验失败时应返回 CrudError::Invalid");
+    };
+    let fields: Vec<&str> = errors.errors().iter().map(|e| e.field).collect();
+    assert_eq!(fields, ["name", "email", "age", "phone"]);
+    assert_eq!(
+        errors
+            .field_errors("age")
+            .next()
+            .unwrap()
+            .message_in("zh-CN"),
+        "必须在 0 到 150 之间"
+    );
+    assert!(matches!(
+        Member::create_many(&pool, std::slice::from_ref(&member)).await,
+        Err(CrudError::Invalid(_))
+    ));
+    assert_eq!(Member::count_by_cond(&pool, &Cond::new()).await.unwrap(), 0);
+
+    // None 的字段不检查
+    member.name = "名字".into();
+    member.email = None;
+    member.age = 150;
+    member.phone = "123".into();
+    member.validate().unwrap();
+    member.id = member.create_returning_id(&pool).await.unwrap();
+    member.age = -1;
+    assert!(matches!(
+        member.save(&pool).await,
+        Err(CrudError::Invalid(_))
+    ));
+}
+
+static HOOK_CALLS: std::sync::Mutex<Vec<&'static str>> = std::sync::Mutex::new(Vec::new());
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", hooks)]
+pub struct Subscriber {
+    pub id: i64,
+    pub email: String,
+}
+
+impl crud_runtime::CrudHooks for Subscriber {
+    async fn before_create(&mut self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("before_create");
+        if self.email.ends_with("@example.com") {
+            return Err(CrudError::Rejected("不允许的邮箱".to_owned()));
+        }
+        self.email = self.email.trim().to_lowercase();
+        Ok(())
+    }
+
+    async fn after_create(&self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("after_create");
+        Ok(())
+    }
+
+    async fn before_save(&mut self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("before_save");
+        self.email = self.email.trim().to_lowercase();
+        Ok(())
+    }
+
+    async fn after_save(&self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("after_save");
+        Ok(())
+    }
+
+    async fn before_delete(&self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("before_delete");
+        Ok(())
+    }
+
+    async fn after_delete(&self) -> Result<(), CrudError> {
+        HOOK_CALLS.lock().unwrap().push("after_delete");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn hooks() {
+    let pool = pool(&[Subscriber::create_table_sql()]).await;
+    let rejected = Subscriber {
+        email: "a@example.com".into(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        rejected.create(&pool).await,
+        Err(CrudError::Rejected(_))
+    ));
+    assert_eq!(
+        Subscriber::count_by_cond(&pool, &Cond::new())
+            .await
+            .unwrap(),
+        0
+    );
+
+    // 写入的是 before_create 修改后的副本, 调用方的值不变
+    let mut sub = Subscriber {
+        email: " A@X.com ".into(),
+        ..Default::default()
+    };
+    let created = sub.create_returning(&pool).await.unwrap();
+    assert_eq!(created.email, "a@x.com");
+    assert_eq!(sub.email, " A@X.com ");
+    sub.id = created.id;
+    sub.email = " B@X.com".into();
+    sub.save(&pool).await.unwrap();
+    assert_eq!(
+        Subscriber::find_by_pk(&pool, sub.id).await.unwrap().email,
+        "b@x.com"
+    );
+    sub.delete(&pool).await.unwrap();
+    assert_eq!(
+        *HOOK_CALLS.lock().unwrap(),
+        [
+            "before_create",
+            "before_create",
+            "after_create",
+            "before_save",
+            "after_save",
+            "before_delete",
+            "after_delete",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn order_by_whitelist() {
+    let pool = pool(&[Person::create_table_sql()]).await;
+    for name in ["b", "a", "c"] {
+        Person {
+            name: name.into(),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+    }
+    // 字段名和列名都可以使用, 转换为带引号的列名
+    let (rows, _) = Person::get_all_by_cond(&pool, &Cond::new().order_by("name DESC"))
+        .await
+        .unwrap();
+    let names: Vec<&str> = rows.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["c", "b", "a"]);
+    let (rows, _) = Person::get_all_by_cond(&pool, &Cond::new().order_by("personId desc"))
+        .await
+        .unwrap();
+    assert_eq!(rows[0].name, "c");
+
+    for order_by in [
+        "cache",
+        "name; DROP TABLE people",
+        "name DESC --",
+        "(SELECT 1)",
+    ] {
+        let cond = Cond::new().order_by(order_by);
+        assert!(
+            matches!(
+                Person::get_all_by_cond(&pool, &cond).await,
+                Err(CrudError::Validation(_))
+            ),
+            "{}",
+            order_by
+        );
+    }
+    assert_eq!(Person::get_all(&pool).await.unwrap().len(), 3);
+
+    // 写入的列名来自列枚举
+    assert_eq!(PersonColumn::Id.as_str(), "\"personId\"");
+    Person::update_by_cond(
+        &pool,
+        &[(PersonColumn::Name, "z".into())],
+        &Cond::new().eq("personId", 1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Person::find_by_pk(&pool, 1).await.unwrap().name, "z");
+}
+
+#[tokio::test]
+async fn typed_columns() {
+    let pool = pool(&[Memo::create_table_sql()]).await;
+    // create_many 把 None 写入 NULL
+    let memos: Vec<Memo> = [("a", Some("x")), ("b", None), ("c", Some("y"))]
+        .into_iter()
+        .map(|(title, note)| Memo {
+            title: title.into(),
+            note: note.map(str::to_owned),
+            ..Default::default()
+        })
+        .collect();
+    Memo::create_many(&pool, &memos).await.unwrap();
+    let c = Memo::columns();
+    let cond = Cond::new()
+        .filter(c.id.gt(1).and(c.note.is_null()).or(c.title.eq("a")))
+        .order_by("title");
+    let (rows, total) = Memo::get_all_by_cond(&pool, &cond).await.unwrap();
+    let titles: Vec<&str> = rows.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!((titles, total), (vec!["a", "b"], 2));
+    assert_eq!(
+        Memo::count_by_cond(&pool, &c.title.in_list(["a", "c"]).into())
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        Memo::count_by_cond(&pool, &c.note.ilike("X").into())
+            .await
+            .unwrap(),
+        1
+    );
+    Memo::delete_by_cond(&pool, &(!c.title.eq("a")).into())
+        .await
+        .unwrap();
+    assert_eq!(Memo::get_all(&pool).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn bind_vals() {
+    use crud_runtime::{BindVal, Dialect, Val};
+
+    let pool = pool(&[Memo::create_table_sql()]).await;
+    Memo::create_many(
+        &pool,
+        &[Memo {
+            title: "a".into(),
+            note: Some("x".into()),
+            ..Default::default()
+        }],
+    )
+    .await
+    .unwrap();
+
+    // 自己拼写的查询使用与生成的方法相同的绑定
+    let cond = Cond::new().eq("title", "a").raw("note <> ?", ["y".into()]);
+    cond.check().unwrap();
+    let sql = format!(
+        "SELECT COUNT(*) FROM memos WHERE {}",
+        cond.build_for(Dialect::Sqlite)
+    );
+    let n: i64 = sqlx::query_scalar(&sql)
+        .bind_vals(&cond.args)
+        .unwrap()
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(n, 1);
+
+    // 数据库不支持的值返回错误, 而不是跳过后让占位符错位
+    let unsupported = Cond::new()
+        .raw("id IN (?)", [Val::array([1, 2])])
+        .eq("title", "a");
+    assert!(matches!(
+        Memo::get_all_by_cond(&pool, &unsupported).await,
+        Err(CrudError::Unsupported(_))
+    ));
+    assert!(matches!(
+        Memo::count_by_cond(&pool, &unsupported).await,
+        Err(CrudError::Unsupported(_))
+    ));
+    // 返回 Option 的方法记录错误日志后返回 None
+    assert!(Memo::get_by_cond(&pool, &unsupported).await.is_none());
+    assert!(Memo::get_by_cond(&pool, &cond).await.is_some());
+
+    Memo::update_by_cond(&pool, &[(MemoColumn::Note, Val::Null)], &cond)
+        .await
+        .unwrap();
+    assert_eq!(
+        Memo::get_by_title(&pool, &"a".to_owned())
+            .await
+            .unwrap()
+            .note,
+        None
+    );
+}
+
+#[tokio::test]
+async fn unsigned_binds() {
+    let pool = pool(&[Account::create_table_sql()]).await;
+    let id = Account {
+        email: "a@x.com".into(),
+        ..Default::default()
+    }
+    .create_returning_id(&pool)
+    .await
+    .unwrap();
+    assert_eq!(id, 1);
+    // 无符号整数绑定为更宽的有符号整数
+    for cond in [
+        Cond::new().eq("id", 1u8),
+        Cond::new().eq("id", 1u16),
+        Cond::new().eq("id", 1u32),
+        Cond::new().eq("id", 1u64),
+    ] {
+        assert_eq!(Account::count_by_cond(&pool, &cond).await.unwrap(), 1);
+    }
+    let cond = Cond::new().raw("id < ?", [u32::MAX.into()]);
+    assert_eq!(Account::count_by_cond(&pool, &cond).await.unwrap(), 1);
+    let cond = Cond::new().raw("id < ?", [(i64::MAX as u64).into()]);
+    assert_eq!(Account::count_by_cond(&pool, &cond).await.unwrap(), 1);
+
+    // 超出 i64 的 u64 返回错误而不是回绕为负数
+    let cond = Cond::new().raw("id < ?", [u64::MAX.into()]);
+    assert!(matches!(
+        Account::count_by_cond(&pool, &cond).await,
+        Err(CrudError::Overflow(u64::MAX))
+    ));
+}
+
+#[derive(Debug, Default, Clone, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Event {
+    pub id: i64,
+    pub key: sqlx::types::Uuid,
+    pub at: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    pub day: sqlx::types::chrono::NaiveDate,
+    pub payload: sqlx::types::JsonValue,
+    pub data: Vec<u8>,
+    pub note: Option<String>,
+}
+
+#[tokio::test]
+async fn val_types() {
+    use crud_runtime::Val;
+    use sqlx::types::chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+    let pool = pool(&[Event::create_table_sql()]).await;
+    let key = sqlx::types::Uuid::from_u128(7);
+    let at: DateTime<Utc> = "2024-01-02T03:04:05Z".parse().unwrap();
+    let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    let payload = sqlx::types::JsonValue::from(vec![1, 2]);
+    Event {
+        key,
+        at,
+        day,
+        payload: payload.clone(),
+        data: vec![0, 1],
+        ..Default::default()
+    }
+    .create(&pool)
+    .await
+    .unwrap();
+
+    // 带时区的时间转换为 UTC 后比较
+    let local: DateTime<FixedOffset> = "2024-01-02T11:04:05+08:00".parse().unwrap();
+    for (column, val) in [
+        ("key", Val::from(key)),
+        ("at", Val::from(local)),
+        ("at", Val::from(at.naive_utc())),
+        ("day", Val::from(day)),
+        ("payload", Val::from(payload)),
+        ("data", Val::from(vec![0u8, 1])),
+    ] {
+        let cond = Cond::new().eq(column, val);
+        assert_eq!(
+            Event::count_by_cond(&pool, &cond).await.unwrap(),
+            1,
+            "{}",
+            column
+        );
+    }
+
+    // Option 转换为 Val, None 为 Null
+    assert_eq!(Val::from(None::<i32>), Val::Null);
+    let cond = Cond::new().eq("note", Some("x"));
+    assert_eq!(Event::count_by_cond(&pool, &cond).await.unwrap(), 0);
+    Event::update_by_cond(
+        &pool,
+        &[(EventColumn::Note, None::<String>.into())],
+        &Cond::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Event::count_by_cond(&pool, &Cond::new().filter(Event::columns().note.is_null()))
+            .await
+            .unwrap(),
+        1
+    );
+
+    // SQLite 不支持 Decimal 和数组
+    for val in [
+        Val::from(sqlx::types::Decimal::new(15, 1)),
+        Val::array([1, 2]),
+    ] {
+        let cond = Cond::new().raw("id = ?", [val]);
+        assert!(matches!(
+            Event::count_by_cond(&pool, &cond).await,
+            Err(CrudError::Unsupported(_))
+        ));
+    }
+}
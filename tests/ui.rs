@@ -0,0 +1,7 @@
+//! 编译期报错
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
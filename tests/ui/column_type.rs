@@ -0,0 +1,14 @@
+use crud_derive::CRUDTable;
+use crud_runtime::Cond;
+
+#[derive(Debug, Default, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Member {
+    pub id: i64,
+    pub age: i32,
+}
+
+fn main() {
+    let c = Member::columns();
+    let _ = Cond::new().filter(c.age.eq("18"));
+}
@@ -0,0 +1,10 @@
+use crud_derive::CRUDTable;
+
+#[derive(Debug, Default, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", soft_delete = "deleted_at")]
+pub struct Customer {
+    pub id: i64,
+    pub name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use crud_derive::CRUDTable;
+
+#[derive(Debug, Default, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite", soft_delete = "deleted_at")]
+pub struct Customer {
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: Option<String>,
+}
+
+fn main() {}
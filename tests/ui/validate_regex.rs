@@ -0,0 +1,11 @@
+use crud_derive::CRUDTable;
+
+#[derive(Debug, Default, sqlx::FromRow, CRUDTable)]
+#[crud(backend = "sqlite")]
+pub struct Member {
+    pub id: i64,
+    #[crud(validate(regex = "^(1"))]
+    pub phone: String,
+}
+
+fn main() {}